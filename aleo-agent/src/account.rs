@@ -1,6 +1,6 @@
 //! Account implementations
 
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
 
 use super::*;
@@ -10,23 +10,82 @@ use once_cell::sync::OnceCell;
 use rand_chacha::rand_core::SeedableRng;
 use rand_chacha::ChaChaRng;
 
+use crate::rng::CryptoRngCore;
+
+/// A container for key material, overwritten with zeros when dropped and redacted when printed.
+///
+/// Only the value held by the container is zeroized: copies of it, such as a key passed by value to
+/// snarkVM, are not. Borrow the secret with [Secret::expose] rather than copying it out.
+#[derive(Clone)]
+pub struct Secret<T: Copy>(T);
+
+impl<T: Copy> Secret<T> {
+    pub fn new(secret: T) -> Self {
+        Self(secret)
+    }
+
+    /// Returns the secret.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Copy> Drop for Secret<T> {
+    fn drop(&mut self) {
+        // SAFETY: `T` is `Copy`, so it has no drop glue and any bit pattern is overwritten in place
+        // without being read again
+        unsafe { zeroize::zeroize_flat_type(&mut self.0) }
+    }
+}
+
+impl<T: Copy> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<redacted>")
+    }
+}
+
+/// An Aleo account.
+///
+/// The private key and view key held by the account are zeroized on drop and never printed: `Debug`
+/// redacts them and `Display` shows the address only. snarkVM takes keys by value in places, such
+/// as when deriving the serial number of a record, and the copies it makes are not zeroized.
+///
+/// An account built from a view key alone is watch-only: it can scan and decrypt records, but every
+/// operation that needs to sign fails.
+///
+/// # Example
+/// ```
+/// use aleo_agent::account::Account;
+///
+/// let account = Account::new().unwrap();
+/// let debug = format!("{account:?}");
+///
+/// assert!(!debug.contains(&account.expose_private_key().unwrap().to_string()));
+/// assert_eq!(account.to_string(), account.address().to_string());
+/// ```
 #[derive(Clone)]
 pub struct Account {
-    private_key: PrivateKey,
-    view_key: ViewKey,
+    private_key: Option<Secret<PrivateKey>>,
+    view_key: Secret<ViewKey>,
     address: Address,
 }
 
 impl Debug for Account {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Account")
-            .field("private_key", &self.private_key.to_string())
-            .field("view_key", &self.view_key.to_string())
+            .field("private_key", &self.private_key)
+            .field("view_key", &self.view_key)
             .field("address", &self.address.to_string())
             .finish()
     }
 }
 
+impl Display for Account {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.address)
+    }
+}
+
 impl Default for Account {
     fn default() -> Account {
         Self::from_seed(Default::default()).unwrap()
@@ -37,16 +96,49 @@ impl Account {
     /// Generates a new `Account` using a random seed.
     pub fn new() -> Result<Self> {
         let (private_key, view_key, address) = generate_keypair()?;
-        Ok(Account {
-            private_key,
-            view_key,
+        Ok(Account::from_keys(private_key, view_key, address))
+    }
+
+    // Wraps the keys of an account
+    pub(crate) fn from_keys(private_key: PrivateKey, view_key: ViewKey, address: Address) -> Self {
+        Account {
+            private_key: Some(Secret::new(private_key)),
+            view_key: Secret::new(view_key),
             address,
-        })
+        }
     }
 
     /// Returns the private key of the account.
+    ///
+    /// # Panics
+    /// If the account is watch-only, see [Account::expose_private_key] which returns an error
+    /// instead.
+    #[deprecated(note = "use `expose_private_key` to make access to the private key explicit")]
     pub fn private_key(&self) -> &PrivateKey {
-        &self.private_key
+        match self.expose_private_key() {
+            Ok(private_key) => private_key,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Returns the private key of the account, or an error if the account is watch-only.
+    ///
+    /// The private key gives full control over the funds of the account: avoid copying, printing or
+    /// logging it.
+    pub fn expose_private_key(&self) -> Result<&PrivateKey> {
+        self.secret_private_key().map(Secret::expose)
+    }
+
+    // Returns the container of the private key, to be cloned without copying the key out
+    pub(crate) fn secret_private_key(&self) -> Result<&Secret<PrivateKey>> {
+        self.private_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("Account {} is watch-only: no signing key", self.address))
+    }
+
+    /// Returns `true` if the account holds no private key.
+    pub fn is_watch_only(&self) -> bool {
+        self.private_key.is_none()
     }
 
     /// Returns the address of the account.
@@ -56,6 +148,11 @@ impl Account {
 
     /// Returns the view key of the account.
     pub fn view_key(&self) -> &ViewKey {
+        self.view_key.expose()
+    }
+
+    // Returns the container of the view key, to be cloned without copying the key out
+    pub(crate) fn secret_view_key(&self) -> &Secret<ViewKey> {
         &self.view_key
     }
 
@@ -77,10 +174,10 @@ impl Account {
     /// let encrypted_key = acc.get_encrypted_key("secret").expect("failed to encrypt key");
     /// let recover_account = Account::from_encrypted_key(&encrypted_key, "secret").expect("failed to decrypt key");
     ///
-    /// assert_eq!(acc.private_key().to_string(), recover_account.private_key().to_string());
+    /// assert_eq!(acc.expose_private_key().unwrap().to_string(), recover_account.expose_private_key().unwrap().to_string());
     /// ```
     pub fn get_encrypted_key(&self, secret: &str) -> Result<Ciphertext> {
-        encrypt_field(&self.private_key.seed(), secret, "private_key")
+        encrypt_field(&self.expose_private_key()?.seed(), secret, "private_key")
     }
 
     /// Signs a message with the private key.
@@ -101,8 +198,14 @@ impl Account {
     /// assert!(acc.verify("hello".as_bytes(), &sig));
     /// ```
     pub fn sign(&self, msg: &[u8]) -> Result<Signature> {
-        let mut rng = ChaChaRng::from_entropy();
-        self.private_key.sign_bytes(msg, &mut rng)
+        self.sign_with_rng(msg, &mut ChaChaRng::from_entropy())
+    }
+
+    /// Signs a message with the private key, drawing the signature nonce from `rng`.
+    ///
+    /// The same message and RNG state produce the same signature.
+    pub fn sign_with_rng(&self, msg: &[u8], mut rng: &mut dyn CryptoRngCore) -> Result<Signature> {
+        self.expose_private_key()?.sign_bytes(msg, &mut rng)
     }
 
     /// Verifies a message signature.
@@ -136,15 +239,11 @@ impl Account {
     /// let mut rng_from_seed = ChaChaRng::seed_from_u64(seed);
     /// let private_key = PrivateKey::new(&mut rng_from_seed).expect("failed to recover private key from seed");
     ///
-    /// assert_eq!(account.private_key().to_string(), private_key.to_string());
+    /// assert_eq!(account.expose_private_key().unwrap().to_string(), private_key.to_string());
     /// ```
     pub fn from_seed(seed: u64) -> Result<Self> {
         let (private_key, view_key, address) = generate_keypair_from_seed(seed)?;
-        Ok(Account {
-            private_key,
-            view_key,
-            address,
-        })
+        Ok(Account::from_keys(private_key, view_key, address))
     }
 
     /// Generates a new `Account` from a private key string.
@@ -158,15 +257,37 @@ impl Account {
     /// let private_key = PrivateKey::from_str("YOUR PRIVATE KEY").unwrap();
     /// let account = Account::from_private_key("YOUR PRIVATE KEY").unwrap();
     ///
-    /// assert_eq!(account.private_key().to_string(), private_key.to_string());
+    /// assert_eq!(account.expose_private_key().unwrap().to_string(), private_key.to_string());
     /// ```
     pub fn from_private_key(key: &str) -> Result<Self> {
         let private_key = PrivateKey::from_str(key)?;
         let view_key = ViewKey::try_from(&private_key)?;
         let address = Address::try_from(&private_key)?;
+        Ok(Account::from_keys(private_key, view_key, address))
+    }
+
+    /// Generates a watch-only `Account` from a view key string.
+    ///
+    /// The account can scan and decrypt records and query balances, but cannot sign. Unspent
+    /// records are found with an external [SerialNumberSource].
+    ///
+    /// # Example
+    /// ```
+    /// use aleo_agent::account::Account;
+    ///
+    /// let account = Account::new().unwrap();
+    /// let watch_only = Account::from_view_key(&account.view_key().to_string()).unwrap();
+    ///
+    /// assert_eq!(watch_only.address(), account.address());
+    /// assert!(watch_only.is_watch_only());
+    /// assert!(watch_only.sign(b"hello").is_err());
+    /// ```
+    pub fn from_view_key(key: &str) -> Result<Self> {
+        let view_key = ViewKey::from_str(key)?;
+        let address = view_key.to_address();
         Ok(Account {
-            private_key,
-            view_key,
+            private_key: None,
+            view_key: Secret::new(view_key),
             address,
         })
     }
@@ -190,18 +311,39 @@ impl Account {
     /// let encrypted_key = acc.get_encrypted_key("SECRET").expect("failed to encrypt key");
     /// let recover_account = Account::from_encrypted_key(&encrypted_key, "secret").expect("failed to decrypt key");
     ///
-    /// assert_eq!(acc.private_key().to_string(), recover_account.private_key().to_string());
+    /// assert_eq!(acc.expose_private_key().unwrap().to_string(), recover_account.expose_private_key().unwrap().to_string());
     /// ```
     pub fn from_encrypted_key(ciphertext: &Ciphertext, secret: &str) -> Result<Self> {
         let seed = decrypt_field(ciphertext, secret, "private_key")?;
         let private_key = PrivateKey::try_from(seed)?;
         let view_key = ViewKey::try_from(&private_key)?;
         let address = Address::try_from(&private_key)?;
-        Ok(Account {
-            private_key,
-            view_key,
-            address,
-        })
+        Ok(Account::from_keys(private_key, view_key, address))
+    }
+}
+
+/// A source of record serial numbers, used to find whether records are spent.
+///
+/// Serial numbers are derived from the private key of the record owner. Watch-only agents can
+/// obtain them from a signing host, and pass them as a map from record commitments to serial
+/// numbers.
+pub trait SerialNumberSource {
+    /// Returns the serial number of the record with the given commitment.
+    fn serial_number(&self, commitment: Field) -> Result<Field>;
+}
+
+impl SerialNumberSource for PrivateKey {
+    // snarkVM takes the private key by value, this copy is not zeroized
+    fn serial_number(&self, commitment: Field) -> Result<Field> {
+        PlaintextRecord::serial_number(*self, commitment)
+    }
+}
+
+impl SerialNumberSource for IndexMap<Field, Field> {
+    fn serial_number(&self, commitment: Field) -> Result<Field> {
+        self.get(&commitment)
+            .copied()
+            .ok_or_else(|| anyhow!("No serial number is known for the record {commitment}"))
     }
 }
 
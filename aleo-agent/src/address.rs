@@ -0,0 +1,282 @@
+//! Address validation and a persistent address book
+//!
+//! Addresses are validated with errors naming what is wrong: the prefix, the length, a character
+//! or the checksum. Programs also have addresses, derived from their [ProgramID]: credits sent to
+//! a program address are only spendable by the program, so transfers to known program addresses
+//! are refused unless explicitly allowed.
+
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{ensure, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::*;
+
+/// The prefix of every Aleo address.
+pub const ADDRESS_PREFIX: &str = "aleo1";
+/// The length of an Aleo address string.
+pub const ADDRESS_LENGTH: usize = 63;
+
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// An error found while validating an address string.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("Address '{address}' must start with '{ADDRESS_PREFIX}'")]
+    Prefix { address: String },
+    #[error("Address '{address}' has {found} characters, expected {ADDRESS_LENGTH}")]
+    Length { address: String, found: usize },
+    #[error("Address '{address}' has an invalid character '{character}' at position {position}")]
+    Character {
+        address: String,
+        character: char,
+        position: usize,
+    },
+    #[error("Address '{address}' has an invalid checksum, it may contain a typo")]
+    Checksum { address: String },
+    #[error("Address '{address}' is not a valid point of the curve")]
+    Point { address: String },
+}
+
+/// Parses an address, reporting precisely why it is invalid.
+///
+/// # Example
+/// ```
+/// use aleo_agent::address::{parse_address, AddressError};
+///
+/// let address = parse_address("aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px");
+/// assert!(address.is_ok());
+///
+/// let typo = parse_address("aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9pq");
+/// assert!(matches!(typo, Err(AddressError::Checksum { .. })));
+/// ```
+pub fn parse_address(address: &str) -> Result<Address, AddressError> {
+    let address = address.trim();
+    let owned = || address.to_string();
+    if !address.starts_with(ADDRESS_PREFIX) {
+        return Err(AddressError::Prefix { address: owned() });
+    }
+    let length = address.chars().count();
+    if length != ADDRESS_LENGTH {
+        return Err(AddressError::Length {
+            address: owned(),
+            found: length,
+        });
+    }
+    if let Some((position, character)) = address
+        .chars()
+        .enumerate()
+        .skip(ADDRESS_PREFIX.len())
+        .find(|(_, character)| !BECH32_CHARSET.contains(*character))
+    {
+        return Err(AddressError::Character {
+            address: owned(),
+            character,
+            position,
+        });
+    }
+    match bech32::decode(address) {
+        Ok((_, _, bech32::Variant::Bech32m)) => {}
+        _ => return Err(AddressError::Checksum { address: owned() }),
+    }
+    Address::from_str(address).map_err(|_| AddressError::Point { address: owned() })
+}
+
+/// Returns the address of a program.
+pub fn program_address(program_id: &ProgramID) -> Result<Address> {
+    program_id.to_address()
+}
+
+/// Returns the program among `program_ids` whose address is `address`, if any.
+pub fn find_program<'a>(
+    address: &Address,
+    program_ids: impl IntoIterator<Item = &'a ProgramID>,
+) -> Option<ProgramID> {
+    program_ids
+        .into_iter()
+        .find(|program_id| program_id.to_address().ok().as_ref() == Some(address))
+        .copied()
+}
+
+/// An entry of an [AddressBook].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressEntry {
+    pub address: Address,
+    /// The program owning the address, for program addresses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<ProgramID>,
+}
+
+/// Labelled addresses of users and programs, stored as a JSON file.
+///
+/// # Example
+/// ```ignore
+/// use aleo_agent::address::AddressBook;
+///
+/// let mut book = AddressBook::load("addresses.json").unwrap_or_default();
+/// book.insert("alice", "aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px").unwrap();
+/// book.insert_program("token", &"token.aleo".parse().unwrap()).unwrap();
+/// book.save("addresses.json").unwrap();
+///
+/// let alice = book.get("alice").unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressBook {
+    entries: IndexMap<String, AddressEntry>,
+}
+
+impl AddressBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads an address book from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the address book to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Validates an address and adds it under a label, replacing any previous entry.
+    pub fn insert<S: Into<String>>(&mut self, label: S, address: &str) -> Result<Address> {
+        let address = parse_address(address)?;
+        self.insert_entry(
+            label.into(),
+            AddressEntry {
+                address,
+                program_id: None,
+            },
+        )?;
+        Ok(address)
+    }
+
+    /// Adds the address of a program under a label, replacing any previous entry.
+    pub fn insert_program<S: Into<String>>(
+        &mut self,
+        label: S,
+        program_id: &ProgramID,
+    ) -> Result<Address> {
+        let address = program_address(program_id)?;
+        self.insert_entry(
+            label.into(),
+            AddressEntry {
+                address,
+                program_id: Some(*program_id),
+            },
+        )?;
+        Ok(address)
+    }
+
+    fn insert_entry(&mut self, label: String, entry: AddressEntry) -> Result<()> {
+        ensure!(!label.is_empty(), "The label must not be empty");
+        self.entries.insert(label, entry);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, label: &str) -> Option<AddressEntry> {
+        self.entries.shift_remove(label)
+    }
+
+    /// Returns the address labelled `label`.
+    pub fn get(&self, label: &str) -> Option<&Address> {
+        self.entries.get(label).map(|entry| &entry.address)
+    }
+
+    /// Returns the label and entry of an address.
+    pub fn find(&self, address: &Address) -> Option<(&str, &AddressEntry)> {
+        self.entries
+            .iter()
+            .find(|(_, entry)| &entry.address == address)
+            .map(|(label, entry)| (label.as_str(), entry))
+    }
+
+    /// Returns the programs of the address book.
+    pub fn program_ids(&self) -> impl Iterator<Item = &ProgramID> {
+        self.entries
+            .values()
+            .filter_map(|entry| entry.program_id.as_ref())
+    }
+
+    /// Returns the entries by label.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AddressEntry)> {
+        self.entries
+            .iter()
+            .map(|(label, entry)| (label.as_str(), entry))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ADDRESS: &str = "aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px";
+
+    #[test]
+    fn test_parse_address() {
+        assert!(parse_address(ADDRESS).is_ok());
+        assert!(matches!(
+            parse_address(&ADDRESS.replace("aleo1", "aleo2")),
+            Err(AddressError::Prefix { .. })
+        ));
+        assert!(matches!(
+            parse_address(&ADDRESS[..62]),
+            Err(AddressError::Length { found: 62, .. })
+        ));
+        assert!(matches!(
+            parse_address(&ADDRESS.replace('d', "b")),
+            Err(AddressError::Character {
+                character: 'b',
+                position: 8,
+                ..
+            })
+        ));
+        // Lengths and positions count characters, not bytes
+        assert!(matches!(
+            parse_address(&format!("{}é", &ADDRESS[..62])),
+            Err(AddressError::Character {
+                character: 'é',
+                position: 62,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_address(&format!("{}q", &ADDRESS[..62])),
+            Err(AddressError::Checksum { .. })
+        ));
+    }
+
+    #[test]
+    fn test_address_book() {
+        let mut book = AddressBook::new();
+        let alice = book.insert("alice", ADDRESS).unwrap();
+        assert!(book.insert("bob", "aleo1").is_err());
+
+        let credits = ProgramID::from_str("credits.aleo").unwrap();
+        let credits_address = book.insert_program("credits", &credits).unwrap();
+        assert_eq!(
+            find_program(&credits_address, book.program_ids()),
+            Some(credits)
+        );
+        assert_eq!(find_program(&alice, book.program_ids()), None);
+        assert_eq!(book.find(&alice).unwrap().0, "alice");
+
+        let json = serde_json::to_string(&book).unwrap();
+        assert_eq!(serde_json::from_str::<AddressBook>(&json).unwrap(), book);
+    }
+}
@@ -1,18 +1,25 @@
 //! The main Agent module. Contains the [Agent] types and all associated structures
 
-use crate::account::Account;
+use crate::account::{Account, SerialNumberSource};
+use crate::address::find_program;
+use crate::backend::Backend;
 use crate::builder::AgentBuilder;
 use crate::program::ProgramManager;
+use crate::rng::CryptoRngCore;
+use crate::signer::Signer;
+use crate::wallet::Wallet;
 use anyhow::{bail, ensure, Result};
+use rand_chacha::ChaChaRng;
 use snarkvm::circuit::prelude::num_traits::ToPrimitive;
 use std::fmt;
 use std::ops::Range;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
 use crate::{
     Address, CiphertextRecord, ConsensusStore, CurrentNetwork, Entry, Field, Identifier, Literal,
-    Plaintext, PlaintextRecord, ProgramID, Query, Transaction, Value, DEFAULT_BASE_URL,
-    DEFAULT_TESTNET, VM,
+    Plaintext, PlaintextRecord, ProgramID, Transaction, Value, DEFAULT_BASE_URL, DEFAULT_TESTNET,
+    VM,
 };
 
 #[derive(Clone)]
@@ -21,6 +28,10 @@ pub struct Agent {
     base_url: String,
     network: String,
     account: Account,
+    wallet: Option<Wallet>,
+    signer: Option<Arc<dyn Signer>>,
+    backend: Option<Arc<dyn Backend>>,
+    rng: Option<Arc<Mutex<ChaChaRng>>>,
 }
 
 impl Default for Agent {
@@ -30,6 +41,10 @@ impl Default for Agent {
             account: Account::default(),
             base_url: DEFAULT_BASE_URL.to_string(),
             network: DEFAULT_TESTNET.to_string(),
+            wallet: None,
+            signer: None,
+            backend: None,
+            rng: None,
         }
     }
 }
@@ -45,6 +60,10 @@ impl Agent {
             base_url,
             network,
             account,
+            wallet: None,
+            signer: None,
+            backend: None,
+            rng: None,
         }
     }
 
@@ -53,10 +72,33 @@ impl Agent {
         Ok(ProgramManager::new(self, program_id))
     }
 
+    /// Returns the account of the agent.
+    ///
+    /// With an external signer, the agent acts for the address of the signer instead: see
+    /// [Agent::address] and [Agent::view_key].
     pub fn account(&self) -> &Account {
         &self.account
     }
 
+    pub fn wallet(&self) -> Option<&Wallet> {
+        self.wallet.as_ref()
+    }
+
+    /// Returns the external signer of the agent, see [Agent::signer].
+    pub fn external_signer(&self) -> Option<&Arc<dyn Signer>> {
+        self.signer.as_ref()
+    }
+
+    /// Returns the backend of the agent, if it does not talk to a node over HTTP.
+    pub fn backend(&self) -> Option<&Arc<dyn Backend>> {
+        self.backend.as_ref()
+    }
+
+    // Returns the seeded stream of the agent, see [Agent::set_rng_seed]
+    pub(crate) fn rng(&self) -> Option<&Arc<Mutex<ChaChaRng>>> {
+        self.rng.as_ref()
+    }
+
     pub fn base_url(&self) -> &String {
         &self.base_url
     }
@@ -81,6 +123,38 @@ impl Agent {
         self.account = account;
     }
 
+    /// Sets the wallet of the agent, whose default account becomes the agent's account.
+    pub fn set_wallet(&mut self, wallet: Wallet) {
+        if let Some(account) = wallet.default_account() {
+            self.account = account.clone();
+        }
+        self.wallet = Some(wallet);
+    }
+
+    /// Signs with an external signer instead of the private key of the agent's account.
+    pub fn set_external_signer(&mut self, signer: Arc<dyn Signer>) {
+        self.signer = Some(signer);
+    }
+
+    /// Signs with the private key of the agent's account again.
+    pub fn remove_external_signer(&mut self) {
+        self.signer = None;
+    }
+
+    /// Talks to a backend, such as an in-process ledger, instead of a node over HTTP.
+    pub fn set_backend(&mut self, backend: Arc<dyn Backend>) {
+        self.backend = Some(backend);
+    }
+
+    /// Talks to the node at the agent's URL again.
+    pub fn remove_backend(&mut self) {
+        self.backend = None;
+    }
+
+    pub(crate) fn set_rng(&mut self, rng: Option<Arc<Mutex<ChaChaRng>>>) {
+        self.rng = rng;
+    }
+
     pub fn local_testnet(&mut self, port: &str) {
         self.network = DEFAULT_TESTNET.to_string();
         self.base_url = format!("http://0.0.0.0:{}", port);
@@ -111,8 +185,7 @@ impl Agent {
         &self,
         ciphertext_record: &CiphertextRecord,
     ) -> Result<PlaintextRecord> {
-        let view_key = self.account().view_key();
-        ciphertext_record.decrypt(view_key)
+        ciphertext_record.decrypt(self.view_key()?)
     }
 
     /// Finds unspent records on chain.
@@ -141,15 +214,46 @@ impl Agent {
         &self,
         block_heights: Range<u32>,
         max_gates: Option<u64>, // microcredits
+    ) -> Result<Vec<(Field, PlaintextRecord)>> {
+        let private_key = self.serial_number_key()?;
+        self.get_unspent_records_with(block_heights, max_gates, private_key)
+    }
+
+    /// Finds unspent records on chain, using an external source of serial numbers.
+    ///
+    /// Watch-only agents use this method, since they cannot derive the serial numbers of their records.
+    /// Records whose serial number is not known to the source are skipped.
+    ///
+    /// # Arguments
+    /// * `block_heights` - The range of block heights to search for unspent records.
+    /// * `max_gates` - The minimum threshold microcredits for the sum of balances collected from records
+    /// * `serial_numbers` - The source of the serial numbers of the records of the agent's account.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use aleo_agent::account::Account;
+    /// use aleo_agent::agent::Agent;
+    /// use indexmap::IndexMap;
+    ///
+    /// let account = Account::from_view_key("AViewKey1...").unwrap();
+    /// let agent = Agent::builder().with_account(account).build();
+    /// // Serial numbers by record commitment, exported by the signing host
+    /// let serial_numbers = IndexMap::new();
+    /// let records = agent.get_unspent_records_with(0..100, None, &serial_numbers).unwrap();
+    /// ```
+    pub fn get_unspent_records_with(
+        &self,
+        block_heights: Range<u32>,
+        max_gates: Option<u64>, // microcredits
+        serial_numbers: &dyn SerialNumberSource,
     ) -> Result<Vec<(Field, PlaintextRecord)>> {
         ensure!(
             block_heights.start < block_heights.end,
             "The start block height must be less than the end block height"
         );
 
-        let private_key = self.account().private_key();
-        let view_key = self.account().view_key();
-        let address_x_coordinate = self.account().address().to_x_coordinate();
+        let view_key = self.view_key()?;
+        let address_x_coordinate = self.address().to_x_coordinate();
 
         let step_size = 49;
 
@@ -172,16 +276,21 @@ impl Agent {
                 .flat_map(|block| block.into_records())
                 .filter_map(|(commitment, record)| {
                     if record.is_owner_with_address_x_coordinate(view_key, &address_x_coordinate) {
-                        let sn = PlaintextRecord::serial_number(*private_key, commitment).ok()?;
-                        if self.find_transition_id_by_input_or_output_id(sn).is_err() {
-                            if let Ok(record) = record.decrypt(view_key) {
-                                total_gates += record.microcredits().unwrap_or(0);
-                                return Some((commitment, record));
+                        let sn = serial_numbers.serial_number(commitment).ok()?;
+                        match self.is_spent(sn) {
+                            Ok(false) => {
+                                if let Ok(record) = record.decrypt(view_key) {
+                                    total_gates += record.microcredits().unwrap_or(0);
+                                    return Some(Ok((commitment, record)));
+                                }
                             }
+                            Ok(true) => {}
+                            Err(error) => return Some(Err(error)),
                         }
                     };
                     None
-                });
+                })
+                .collect::<Result<Vec<_>>>()?;
 
             // Filter the records by the view key.
             records.extend(_records);
@@ -231,7 +340,8 @@ impl Agent {
         max_records: Option<usize>,
     ) -> Result<Vec<(Field, PlaintextRecord)>> {
         // Compute the x-coordinate of the address.
-        let address_x_coordinate = self.account().address().to_x_coordinate();
+        let view_key = self.view_key()?;
+        let address_x_coordinate = self.address().to_x_coordinate();
 
         // Prepare the starting block height, by rounding down to the nearest step of 50.
         let start_block_height = block_heights.start - (block_heights.start % 50);
@@ -256,7 +366,6 @@ impl Agent {
                 end
             };
 
-            let view_key = self.account().view_key();
             // Filter the records by the view key.
             let _records = self
                 .get_blocks_in_range(start_height, end_height)?
@@ -293,19 +402,19 @@ impl Agent {
     /// * a `u64` - The public balance in microcredits associated with the address.
     /// * an `Error` - If there was an issue fetching the public balance.
     pub fn get_public_balance(&self) -> Result<u64> {
+        self.get_public_balance_of(&self.address())
+    }
+
+    /// Fetch the public balance in microcredits of any address.
+    ///
+    /// # Arguments
+    /// * `address` - The address to fetch the public balance of.
+    pub fn get_public_balance_of(&self, address: &Address) -> Result<u64> {
         let credits = ProgramID::from_str("credits.aleo")?;
         let account_mapping = Identifier::from_str("account")?;
-        let url = format!(
-            "{}/{}/program/{}/mapping/{}/{}",
-            self.base_url(),
-            self.network(),
-            credits,
-            account_mapping,
-            self.account().address()
-        );
-        let response = self.client().get(&url).call()?;
-        Ok(response
-            .into_json::<Option<Value>>()?
+        let path = format!("/program/{credits}/mapping/{account_mapping}/{address}");
+        Ok(self
+            .get_json::<Option<Value>>(&path)?
             .and_then(|value| match value {
                 //Value::Plaintext(Plaintext::Literal(Literal::U64(amount), _))
                 Value::Plaintext(Plaintext::Literal(Literal::U64(amount), _)) => {
@@ -322,14 +431,12 @@ impl Agent {
     /// A `Result` which is:
     /// * a `Vec<Transaction>` - The transactions associated with the agent's account.
     /// * an `Error` - If there was an issue fetching the transactions.
+    #[deprecated(
+        note = "the `/address` endpoint is not served by every node, use `history` instead"
+    )]
     pub fn get_transactions(&self) -> Result<Vec<Transaction>> {
-        let url = format!(
-            "{}/{}/address/{}",
-            self.base_url(),
-            self.network(),
-            self.account().address()
-        );
-        match self.client().get(&url).call()?.into_json() {
+        let path = format!("/address/{}", self.address());
+        match self.get_json(&path) {
             Ok(transaction) => Ok(transaction),
             Err(error) => bail!("Failed to get account transactions : {error}"),
         }
@@ -368,6 +475,21 @@ impl Agent {
     /// let transfer_result = agent.transfer(transfer_args);
     /// ```
     pub fn transfer(&self, args: TransferArgs) -> Result<String> {
+        self.with_rng(|rng| self.transfer_with_rng(args, rng))
+    }
+
+    /// Transfers credits, drawing the randomness of the transaction from `rng`.
+    ///
+    /// The same RNG state, arguments and ledger state produce the same transaction.
+    ///
+    /// # Errors
+    /// If the recipient is the address of `credits.aleo` or of a program given to
+    /// [TransferArgs::with_known_programs], unless [TransferArgs::allow_program_recipient] was called.
+    pub fn transfer_with_rng(
+        &self,
+        args: TransferArgs,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<String> {
         match &(args.transfer_type) {
             TransferType::Private(from_record) | TransferType::PrivateToPublic(from_record) => {
                 ensure!(
@@ -385,22 +507,30 @@ impl Agent {
             );
         }
 
+        let credits = ProgramID::from_str("credits.aleo")?;
+        if !args.allow_program_recipient {
+            if let Some(warning) =
+                args.recipient_warning([&credits].into_iter().chain(&args.known_programs))
+            {
+                bail!(
+                    "{warning}: call `TransferArgs::allow_program_recipient` to send them anyway"
+                );
+            }
+        }
+
         let inputs = args.to_inputs();
-        let transfer_function = args.transfer_type.to_string();
-        let rng = &mut rand::thread_rng();
+        let transfer_function = Identifier::from_str(&args.transfer_type.to_string())?;
         // Initialize a VM
         let store = ConsensusStore::open(None)?;
         let vm = VM::from(store)?;
-        // Specify the network state query
-        let query = Query::from(self.base_url().clone());
-        // Create a new transaction.
-        let execution = vm.execute(
-            self.account().private_key(),
-            ("credits.aleo", transfer_function),
-            inputs.iter(),
-            args.fee_record,
+        // Create a new transaction, signed by the agent's signer
+        let execution = self.create_execute_transaction(
+            &vm,
+            &credits,
+            &transfer_function,
+            &inputs,
             args.priority_fee,
-            Some(query),
+            args.fee_record,
             rng,
         )?;
         self.broadcast_transaction(&execution)
@@ -458,6 +588,8 @@ pub struct TransferArgs {
     recipient_address: Address,
     transfer_type: TransferType,
     fee_record: Option<PlaintextRecord>,
+    known_programs: Vec<ProgramID>,
+    allow_program_recipient: bool,
 }
 
 impl TransferArgs {
@@ -496,9 +628,59 @@ impl TransferArgs {
             recipient_address,
             transfer_type,
             fee_record,
+            known_programs: vec![],
+            allow_program_recipient: false,
         }
     }
 
+    /// Refuses to transfer to the addresses of `program_ids`, in addition to `credits.aleo`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use aleo_agent::address::AddressBook;
+    ///
+    /// let book = AddressBook::load("addresses.json").unwrap();
+    /// let args = TransferArgs::from(amount, recipient, 0, None, TransferType::Public)
+    ///     .with_known_programs(book.program_ids().copied());
+    /// ```
+    pub fn with_known_programs(mut self, program_ids: impl IntoIterator<Item = ProgramID>) -> Self {
+        self.known_programs.extend(program_ids);
+        self
+    }
+
+    /// Transfers even if the recipient is the address of a known program.
+    pub fn allow_program_recipient(mut self) -> Self {
+        self.allow_program_recipient = true;
+        self
+    }
+
+    /// Returns a warning if the recipient is the address of one of the given programs.
+    ///
+    /// Credits sent to a program address can only be spent by the program itself.
+    ///
+    /// # Example
+    /// ```
+    /// use std::str::FromStr;
+    /// use aleo_agent::agent::{TransferArgs, TransferType};
+    /// use aleo_agent::ProgramID;
+    ///
+    /// let credits = ProgramID::from_str("credits.aleo").unwrap();
+    /// let recipient = credits.to_address().unwrap();
+    /// let args = TransferArgs::from(1, recipient, 0, None, TransferType::Public);
+    /// assert!(args.recipient_warning([&credits]).is_some());
+    /// ```
+    pub fn recipient_warning<'a>(
+        &self,
+        program_ids: impl IntoIterator<Item = &'a ProgramID>,
+    ) -> Option<String> {
+        find_program(&self.recipient_address, program_ids).map(|program_id| {
+            format!(
+                "The recipient {} is the address of the program {program_id}, credits sent to it can only be spent by the program",
+                self.recipient_address
+            )
+        })
+    }
+
     /// Convert the transfer arguments to a vector of values.
     ///
     /// # Returns
@@ -0,0 +1,152 @@
+//! Delegated proving of authorizations
+//!
+//! Creating an [Authorization] only takes a few signatures, while proving it is heavy. A thin
+//! client authorizes a call and its fee, and sends both to a prover, which creates the transaction
+//! with [Agent::execute_authorization]. Authorizations are serialized with their `Display` and
+//! `FromStr` implementations, or with serde.
+//!
+//! # Example
+//! ```ignore
+//! use aleo_agent::agent::Agent;
+//! use aleo_agent::MICROCREDITS;
+//!
+//! // On the client, which holds the signing key
+//! let client = Agent::default();
+//! let pm = client.program("xxx.aleo").unwrap();
+//! let authorization = pm.authorize("main", vec!["1u32", "2u32"].into_iter()).unwrap();
+//! let fee_authorization = client.authorize_fee(&authorization, MICROCREDITS, 0, None).unwrap();
+//!
+//! // On the prover, which holds no key
+//! let prover = Agent::default();
+//! let transaction = prover
+//!     .execute_authorization(authorization, Some(fee_authorization))
+//!     .unwrap();
+//! prover.broadcast_transaction(&transaction).unwrap();
+//! ```
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, ensure, Result};
+use snarkvm::synthesizer::process::execution_cost;
+
+use crate::agent::Agent;
+use crate::program::ProgramManager;
+use crate::validation::{validate_inputs, InputError};
+
+use super::*;
+
+impl<'agent> ProgramManager<'agent> {
+    /// Authorizes a call to a program function with the agent's signer, without proving it.
+    ///
+    /// The inputs are validated against the function signature, as for
+    /// [execute_program](ProgramManager::execute_program).
+    ///
+    /// # Arguments
+    /// * `function` - The function to call
+    /// * `inputs` - The inputs to the function
+    ///
+    /// # Returns
+    /// The authorization of the call, to be proved with [Agent::execute_authorization]
+    pub fn authorize(
+        &self,
+        function: &str,
+        inputs: impl ExactSizeIterator<Item = impl TryInto<Value>>,
+    ) -> Result<Authorization> {
+        let function_id: Identifier =
+            Identifier::from_str(function).map_err(|_| anyhow!("Invalid function name"))?;
+        let program = self.agent().get_program(self.program_id())?;
+
+        let signer = self.agent().signer()?;
+        let inputs = inputs
+            .enumerate()
+            .map(|(index, input)| input.try_into().map_err(|_| InputError::Parse { index }))
+            .collect::<Result<Vec<Value>, InputError>>()?;
+        validate_inputs(&program, &function_id, &inputs, &signer.address())?;
+
+        let vm = self.initialize_vm(&program)?;
+        let process = vm.process();
+        let process = process.read();
+        self.agent()
+            .with_rng(|rng| signer.authorize(&process, program.id(), &function_id, &inputs, rng))
+    }
+}
+
+impl Agent {
+    /// Authorizes the fee of an execution with the agent's signer, without proving it.
+    ///
+    /// The base fee must cover the execution cost, which is checked by
+    /// [execute_authorization](Agent::execute_authorization) once the execution is proved.
+    ///
+    /// # Arguments
+    /// * `authorization` - The authorization of the execution
+    /// * `base_fee` - The base fee in microcredits
+    /// * `priority_fee` - The priority fee in microcredits
+    /// * `fee_record` - The record paying the fee. If None, the fee is paid from the public balance
+    pub fn authorize_fee(
+        &self,
+        authorization: &Authorization,
+        base_fee: u64,
+        priority_fee: u64,
+        fee_record: Option<PlaintextRecord>,
+    ) -> Result<Authorization> {
+        let signer = self.signer()?;
+        let execution_id = authorization.to_execution_id()?;
+        let process = Process::load()?;
+        self.with_rng(|rng| match fee_record {
+            Some(fee_record) => signer.authorize_fee_private(
+                &process,
+                fee_record,
+                base_fee,
+                priority_fee,
+                execution_id,
+                rng,
+            ),
+            None => {
+                signer.authorize_fee_public(&process, base_fee, priority_fee, execution_id, rng)
+            }
+        })
+    }
+
+    /// Proves an authorization and its fee authorization into an execution transaction.
+    ///
+    /// No signing key is needed. The programs called by the authorization are fetched from the
+    /// network.
+    ///
+    /// # Arguments
+    /// * `authorization` - The authorization of the execution
+    /// * `fee_authorization` - The authorization of the fee, required unless the execution is free
+    ///
+    /// # Returns
+    /// The transaction, ready to be broadcast
+    pub fn execute_authorization(
+        &self,
+        authorization: Authorization,
+        fee_authorization: Option<Authorization>,
+    ) -> Result<Transaction> {
+        let program_id = *authorization.peek_next()?.program_id();
+        let vm = if program_id == ProgramID::from_str("credits.aleo")? {
+            VM::from(ConsensusStore::open(None)?)?
+        } else {
+            ProgramManager::new(self, program_id).initialize_vm(&self.get_program(&program_id)?)?
+        };
+
+        let query = self.query()?;
+        let transaction = self.with_rng(|mut rng| {
+            vm.execute_authorization(authorization, fee_authorization, Some(query), &mut rng)
+        })?;
+
+        let Transaction::Execute(_, execution, fee) = &transaction else {
+            bail!("Expected an execution transaction");
+        };
+        let (cost, _) = execution_cost(&vm.process().read(), execution)?;
+        let base_fee = match fee {
+            Some(fee) => *fee.base_amount()?,
+            None => 0,
+        };
+        ensure!(
+            base_fee >= cost,
+            "The execution costs {cost} microcredits, but the base fee is {base_fee} microcredits"
+        );
+        Ok(transaction)
+    }
+}
@@ -0,0 +1,441 @@
+//! Backends serving the node API without HTTP
+//!
+//! By default, the agent talks to a node over its REST API. A [Backend] answers the same requests
+//! in-process: the [LocalLedger] runs a snarkVM ledger, in memory or in RocksDB with the `rocks`
+//! feature, and produces a block as soon as a transaction is submitted. Simulations run thousands
+//! of executions against it, deterministically since its blocks are produced from a seed.
+//!
+//! # Example
+//! ```ignore
+//! use std::sync::Arc;
+//! use aleo_agent::account::Account;
+//! use aleo_agent::agent::Agent;
+//! use aleo_agent::backend::MemoryLedger;
+//! use aleo_agent::MICROCREDITS;
+//!
+//! let ledger = Arc::new(MemoryLedger::new(0).unwrap());
+//! let account = Account::new().unwrap();
+//! ledger.fund(account.address(), 100 * MICROCREDITS).unwrap();
+//!
+//! let agent = Agent::builder().with_account(account).with_backend(ledger.clone()).build();
+//! let pm = agent.program("credits.aleo").unwrap();
+//! ```
+
+use std::collections::HashMap;
+#[cfg(feature = "rocks")]
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{bail, Result};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+#[cfg(feature = "rocks")]
+use snarkvm::ledger::store::BlockStore;
+use snarkvm::ledger::store::ConsensusStorage;
+use thiserror::Error;
+
+use crate::agent::Agent;
+
+use super::*;
+
+/// A ledger backend of the agent, answering the requests of the node REST API.
+pub trait Backend: Send + Sync {
+    /// Answers a GET request to the node REST API with JSON.
+    ///
+    /// The path excludes the base URL and the network, e.g. `/block/height/latest`.
+    fn get(&self, path: &str) -> Result<String>;
+
+    /// Submits a transaction, returning its ID as JSON, as a node does.
+    fn broadcast(&self, transaction: &Transaction) -> Result<String>;
+
+    /// Returns the query used to prove transactions against the ledger.
+    fn query(&self) -> Result<Query>;
+}
+
+/// The error of a [Backend] request for a resource that does not exist, as a node answers 404.
+#[derive(Debug, Error)]
+#[error("Not found: {0}")]
+pub struct NotFound(pub String);
+
+/// Returns `true` if a request failed because the resource does not exist, rather than because the
+/// node or backend could not be reached or failed.
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<ureq::Error>(),
+        Some(ureq::Error::Status(404, _))
+    ) || error.is::<NotFound>()
+}
+
+impl Agent {
+    // Fetches JSON from the backend, or from the node over HTTP
+    pub(crate) fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        match self.backend() {
+            Some(backend) => Ok(serde_json::from_str(&backend.get(path)?)?),
+            None => {
+                let url = format!("{}/{}{path}", self.base_url(), self.network());
+                Ok(self.client().get(&url).call()?.into_json()?)
+            }
+        }
+    }
+
+    // Returns the query used to prove transactions, see [Backend::query]
+    pub(crate) fn query(&self) -> Result<Query> {
+        match self.backend() {
+            Some(backend) => backend.query(),
+            None => Ok(Query::from(self.base_url())),
+        }
+    }
+}
+
+type Ledger<C> = snarkvm::ledger::Ledger<CurrentNetwork, C>;
+
+/// A local ledger, whose blocks are produced by a single validator derived from a seed.
+pub struct LocalLedger<C: ConsensusStorage<CurrentNetwork>> {
+    ledger: Ledger<C>,
+    validator_key: PrivateKey,
+    mempool: Mutex<Vec<Transaction>>,
+    rng: Mutex<ChaChaRng>,
+    auto_advance: AtomicBool,
+    // The blocks copied to memory for proving, and the height of the next block to copy
+    #[cfg(feature = "rocks")]
+    query_store: Mutex<(BlockStore<CurrentNetwork, BlockMemory<CurrentNetwork>>, u32)>,
+}
+
+/// A local ledger stored in memory.
+pub type MemoryLedger = LocalLedger<ConsensusMemory>;
+
+/// A local ledger stored in RocksDB.
+#[cfg(feature = "rocks")]
+pub type RocksLedger =
+    LocalLedger<snarkvm::ledger::store::helpers::rocksdb::ConsensusDB<CurrentNetwork>>;
+
+impl MemoryLedger {
+    /// Creates a ledger in memory.
+    ///
+    /// # Arguments
+    /// * `seed` - The seed of the validator key and of the block production
+    pub fn new(seed: u64) -> Result<Self> {
+        Self::load(seed, |genesis| Ledger::load(genesis, None::<u16>.into()))
+    }
+}
+
+#[cfg(feature = "rocks")]
+impl RocksLedger {
+    /// Opens a ledger in RocksDB, creating it if needed.
+    ///
+    /// Transactions are proved against a copy of the blocks in memory, made on the first proof and
+    /// then kept up to date, see [Backend::query].
+    ///
+    /// # Arguments
+    /// * `seed` - The seed of the validator key and of the block production, the same on each open
+    /// * `dev` - The development ID of the storage, which is kept in the snarkVM storage directory
+    pub fn open(seed: u64, dev: u16) -> Result<Self> {
+        Self::load(seed, |genesis| Ledger::load(genesis, Some(dev).into()))
+    }
+
+    /// Opens a ledger in RocksDB in the directory `path`, creating it if needed.
+    ///
+    /// See [open](RocksLedger::open) for the arguments other than `path`.
+    pub fn open_at<P: AsRef<Path>>(seed: u64, path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        Self::load(seed, |genesis| Ledger::load(genesis, path.into()))
+    }
+}
+
+impl<C: ConsensusStorage<CurrentNetwork>> LocalLedger<C> {
+    // Loads the ledger with `open` from a genesis block derived from the seed
+    fn load(seed: u64, open: impl FnOnce(Block) -> Result<Ledger<C>>) -> Result<Self> {
+        let mut rng = ChaChaRng::seed_from_u64(seed);
+        let validator_key = PrivateKey::new(&mut rng)?;
+        let genesis =
+            VM::from(ConsensusStore::open(None)?)?.genesis_beacon(&validator_key, &mut rng)?;
+        Ok(Self {
+            ledger: open(genesis)?,
+            validator_key,
+            mempool: Mutex::new(vec![]),
+            rng: Mutex::new(rng),
+            auto_advance: AtomicBool::new(true),
+            #[cfg(feature = "rocks")]
+            query_store: Mutex::new((BlockStore::open(None)?, 0)),
+        })
+    }
+
+    /// Returns the snarkVM ledger.
+    pub fn ledger(&self) -> &Ledger<C> {
+        &self.ledger
+    }
+
+    /// Returns the address of the validator, which holds the credits not bonded at genesis.
+    pub fn validator_address(&self) -> Result<Address> {
+        Address::try_from(&self.validator_key)
+    }
+
+    /// Submits a public transfer of credits from the validator.
+    pub fn fund(&self, address: &Address, microcredits: u64) -> Result<TransactionID> {
+        let inputs = [
+            Value::from_str(&address.to_string())?,
+            Value::from_str(&format!("{microcredits}u64"))?,
+        ];
+        let transaction = self.ledger.vm().execute(
+            &self.validator_key,
+            ("credits.aleo", "transfer_public"),
+            inputs.into_iter(),
+            None,
+            0,
+            None,
+            &mut *self.rng.lock().unwrap(),
+        )?;
+        self.submit(transaction)
+    }
+
+    /// Checks a transaction and adds it to the mempool, producing a block if auto advance is on.
+    pub fn submit(&self, transaction: Transaction) -> Result<TransactionID> {
+        self.ledger
+            .vm()
+            .check_transaction(&transaction, None, &mut *self.rng.lock().unwrap())?;
+        let transaction_id = transaction.id();
+        self.mempool.lock().unwrap().push(transaction);
+        if self.auto_advance.load(Ordering::SeqCst) {
+            self.advance()?;
+        }
+        Ok(transaction_id)
+    }
+
+    /// Produces a block with the transactions of the mempool.
+    pub fn advance(&self) -> Result<Block> {
+        let transactions = std::mem::take(&mut *self.mempool.lock().unwrap());
+        let rng = &mut *self.rng.lock().unwrap();
+        let block = self.ledger.prepare_advance_to_next_beacon_block(
+            &self.validator_key,
+            vec![],
+            vec![],
+            transactions,
+            rng,
+        )?;
+        self.ledger.check_next_block(&block, rng)?;
+        self.ledger.advance_to_next_block(&block)?;
+        Ok(block)
+    }
+
+    /// Produces a block for each submitted transaction, which is the default. When off,
+    /// transactions wait in the mempool until [LocalLedger::advance] is called.
+    pub fn set_auto_advance(&self, auto_advance: bool) {
+        self.auto_advance.store(auto_advance, Ordering::SeqCst);
+    }
+
+    /// Returns the height of the latest block.
+    pub fn height(&self) -> u32 {
+        self.ledger.latest_height()
+    }
+
+    /// Returns the transactions waiting for the next block.
+    pub fn mempool(&self) -> Vec<Transaction> {
+        self.mempool.lock().unwrap().clone()
+    }
+
+    /// Answers a GET request to the node REST API, see [Backend::get].
+    pub fn get_json(&self, path: &str) -> Result<String> {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+        let ledger = &self.ledger;
+        let store = ledger.vm().finalize_store();
+
+        match segments.as_slice() {
+            ["block", "height", "latest"] => json(&ledger.latest_height()),
+            ["block", "hash", "latest"] => json(&ledger.latest_hash()),
+            ["block", "latest"] => json(&ledger.latest_block()),
+            ["block", height] => json(&ledger.get_block(height.parse()?)?),
+            ["block", height, "transactions"] => json(&ledger.get_transactions(height.parse()?)?),
+            ["blocks"] => {
+                let params = query
+                    .split('&')
+                    .filter_map(|param| param.split_once('='))
+                    .collect::<HashMap<_, _>>();
+                let (Some(start), Some(end)) = (params.get("start"), params.get("end")) else {
+                    bail!("Missing the start or end height");
+                };
+                let blocks = (start.parse()?..end.parse()?)
+                    .map(|height| ledger.get_block(height))
+                    .collect::<Result<Vec<_>>>()?;
+                json(&blocks)
+            }
+            ["transaction", "confirmed", id] => {
+                json(&ledger.get_confirmed_transaction(TransactionID::from_str(id)?)?)
+            }
+            ["transaction", id] => json(&ledger.get_transaction(TransactionID::from_str(id)?)?),
+            ["find", "blockHash", id] => {
+                json(&ledger.find_block_hash(&TransactionID::from_str(id)?)?)
+            }
+            ["find", "transactionID", "deployment", program_id] => json(
+                &ledger.find_transaction_id_from_program_id(&ProgramID::from_str(program_id)?)?,
+            ),
+            ["find", "transitionID", id] => {
+                // The transition store reports an unknown ID as an error
+                match ledger.find_transition_id(&Field::from_str(id)?) {
+                    Ok(transition_id) => json(&transition_id),
+                    Err(_) => Err(NotFound(path.to_string()).into()),
+                }
+            }
+            ["program", program_id] => json(&ledger.get_program(ProgramID::from_str(program_id)?)?),
+            ["program", program_id, "mappings"] => {
+                json(&store.get_mapping_names_confirmed(&ProgramID::from_str(program_id)?)?)
+            }
+            ["program", program_id, "mapping", mapping, key] => json(&store.get_value_confirmed(
+                ProgramID::from_str(program_id)?,
+                Identifier::from_str(mapping)?,
+                &Plaintext::from_str(key)?,
+            )?),
+            ["stateRoot", "latest"] => json(&ledger.latest_state_root()),
+            ["statePath", commitment] => {
+                json(&ledger.get_state_path_for_commitment(&Field::from_str(commitment)?)?)
+            }
+            // A node answers 404 to requests it does not serve
+            _ => Err(NotFound(format!("unsupported request {path}")).into()),
+        }
+    }
+}
+
+fn json<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+impl Backend for MemoryLedger {
+    fn get(&self, path: &str) -> Result<String> {
+        self.get_json(path)
+    }
+
+    fn broadcast(&self, transaction: &Transaction) -> Result<String> {
+        json(&self.submit(transaction.clone())?)
+    }
+
+    fn query(&self) -> Result<Query> {
+        Ok(Query::from(self.ledger.vm().block_store().clone()))
+    }
+}
+
+// A backend of a node that has no blocks yet, answering block ranges with an empty list
+#[cfg(test)]
+pub(crate) struct NoBlocks;
+
+#[cfg(test)]
+impl Backend for NoBlocks {
+    fn get(&self, _path: &str) -> Result<String> {
+        Ok("[]".to_string())
+    }
+
+    fn broadcast(&self, _transaction: &Transaction) -> Result<String> {
+        bail!("The backend has no ledger")
+    }
+
+    fn query(&self) -> Result<Query> {
+        bail!("The backend has no ledger")
+    }
+}
+
+#[cfg(feature = "rocks")]
+impl Backend for RocksLedger {
+    fn get(&self, path: &str) -> Result<String> {
+        self.get_json(path)
+    }
+
+    fn broadcast(&self, transaction: &Transaction) -> Result<String> {
+        json(&self.submit(transaction.clone())?)
+    }
+
+    // The query of the agent reads blocks from memory, the new blocks are copied there first
+    fn query(&self) -> Result<Query> {
+        let mut query_store = self.query_store.lock().unwrap();
+        let (store, next_height) = &mut *query_store;
+        while *next_height <= self.ledger.latest_height() {
+            store.insert(&self.ledger.get_block(*next_height)?)?;
+            *next_height += 1;
+        }
+        Ok(Query::from(store.clone()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::account::Account;
+    use crate::agent::{TransferArgs, TransferType};
+
+    #[test]
+    fn test_memory_ledger() {
+        let ledger = Arc::new(MemoryLedger::new(0).unwrap());
+        let alice = Account::from_seed(1).unwrap();
+        let bob = Account::from_seed(2).unwrap();
+        ledger.fund(alice.address(), 10 * MICROCREDITS).unwrap();
+        assert_eq!(ledger.height(), 1);
+
+        let agent = Agent::builder()
+            .with_account(alice)
+            .with_backend(ledger.clone())
+            .build();
+        assert_eq!(agent.get_latest_block_height().unwrap(), 1);
+        assert_eq!(agent.get_public_balance().unwrap(), 10 * MICROCREDITS);
+
+        let args = TransferArgs::from(MICROCREDITS, *bob.address(), 0, None, TransferType::Public);
+        agent.transfer(args).unwrap();
+        assert_eq!(ledger.height(), 2);
+        assert_eq!(
+            agent.get_public_balance_of(bob.address()).unwrap(),
+            MICROCREDITS
+        );
+
+        // Credits sent to a known program address are refused unless allowed
+        let token = ProgramID::from_str("token.aleo").unwrap();
+        let recipient = token.to_address().unwrap();
+        let args = TransferArgs::from(MICROCREDITS, recipient, 0, None, TransferType::Public)
+            .with_known_programs([token]);
+        assert!(agent.transfer(args.clone()).is_err());
+        assert_eq!(ledger.height(), 2);
+        agent.transfer(args.allow_program_recipient()).unwrap();
+        assert_eq!(ledger.height(), 3);
+    }
+
+    #[cfg(feature = "rocks")]
+    #[test]
+    fn test_rocks_ledger() {
+        // Removes the storage of the test, even if it fails
+        struct RemoveDir(std::path::PathBuf);
+
+        impl Drop for RemoveDir {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_dir_all(&self.0);
+            }
+        }
+
+        // A storage per run, the blocks of a previous run being kept by RocksDB
+        let storage = RemoveDir(
+            std::env::temp_dir().join(format!("rocks_ledger_test_{}", std::process::id())),
+        );
+        let ledger = Arc::new(RocksLedger::open_at(0, &storage.0).unwrap());
+        let alice = Account::from_seed(1).unwrap();
+        let bob = Account::from_seed(2).unwrap();
+        ledger.fund(alice.address(), 10 * MICROCREDITS).unwrap();
+
+        let agent = Agent::builder()
+            .with_account(alice)
+            .with_backend(ledger.clone())
+            .build();
+        for expected in 1..=2 {
+            let args =
+                TransferArgs::from(MICROCREDITS, *bob.address(), 0, None, TransferType::Public);
+            agent.transfer(args).unwrap();
+            assert_eq!(
+                agent.get_public_balance_of(bob.address()).unwrap(),
+                expected * MICROCREDITS
+            );
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Balance reporting across public, private and staked credits
+//!
+//! The public balance, bonded and unbonding amounts are read from the `account`, `bonded` and
+//! `unbonding` mappings of `credits.aleo`. The private balance is the sum of the unspent
+//! `credits.aleo` records found by scanning blocks with the view key.
+//!
+//! # Example
+//! ```ignore
+//! use aleo_agent::agent::Agent;
+//!
+//! let agent = Agent::default();
+//! let latest_height = agent.get_latest_block_height().unwrap();
+//! let balance = agent.get_balance(0..latest_height + 1).unwrap();
+//! println!("{balance}");
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use crate::account::SerialNumberSource;
+use crate::agent::{Agent, Credits};
+
+use super::*;
+
+/// The number of blocks a record must be buried under to be counted as private rather than pending.
+///
+/// Aleo blocks are final once produced, so this is not a finality threshold: it is a convention of
+/// this crate, reporting the credits received in the last blocks apart, as recently received.
+/// Records still in the mempool are not part of any block and are not counted.
+pub const PENDING_CONFIRMATIONS: u32 = 10;
+
+// The number of blocks fetched per request
+const BATCH_SIZE: u32 = 50;
+
+/// The balance of an address, in microcredits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Balance {
+    /// The balance of the `account` mapping.
+    pub public: u64,
+    /// The unspent records buried under at least [PENDING_CONFIRMATIONS] blocks.
+    pub private: u64,
+    /// The unspent records of the last [PENDING_CONFIRMATIONS] blocks.
+    pub pending: u64,
+    /// The records whose serial number is unknown, which may be spent. They are not counted in
+    /// the other amounts.
+    pub unchecked: u64,
+    /// The amount bonded to a validator.
+    pub bonded: u64,
+    /// The amount being unbonded.
+    pub unbonding: u64,
+    /// The block height from which the unbonding amount can be claimed.
+    pub unbonding_height: Option<u32>,
+}
+
+impl Balance {
+    /// Returns the sum of all the amounts of the balance.
+    pub fn total(&self) -> u64 {
+        self.public + self.private + self.pending + self.bonded + self.unbonding
+    }
+
+    /// Returns the amount that can be spent right away.
+    pub fn spendable(&self) -> u64 {
+        self.public + self.private
+    }
+}
+
+impl fmt::Display for Balance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Public:    {}", credits(self.public))?;
+        writeln!(f, "Private:   {}", credits(self.private))?;
+        writeln!(f, "Pending:   {}", credits(self.pending))?;
+        writeln!(f, "Bonded:    {}", credits(self.bonded))?;
+        write!(f, "Unbonding: {}", credits(self.unbonding))?;
+        if let Some(height) = self.unbonding_height {
+            write!(f, " (claimable at block {height})")?;
+        }
+        if self.unchecked > 0 {
+            write!(
+                f,
+                "\nUnchecked: {} (spent status unknown)",
+                credits(self.unchecked)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Agent {
+    /// Fetches the balance of the agent's account.
+    ///
+    /// With an external signer, the serial numbers of the records cannot be derived and the records
+    /// are reported as [unchecked](Balance::unchecked). If the signer discloses no view key either,
+    /// the records cannot be found: only the public, bonded and unbonding amounts are fetched.
+    ///
+    /// # Arguments
+    /// * `block_heights` - The range of block heights to scan for private records
+    pub fn get_balance(&self, block_heights: Range<u32>) -> Result<Balance> {
+        let Ok(view_key) = self.view_key() else {
+            return self.get_mapping_balance_of(&self.address());
+        };
+        match self.serial_number_key() {
+            Ok(private_key) => self.get_balance_of(view_key, private_key, block_heights),
+            Err(_) => {
+                self.get_balance_of(view_key, &IndexMap::<Field, Field>::new(), block_heights)
+            }
+        }
+    }
+
+    /// Fetches the balance of any address, given its view key.
+    ///
+    /// Records whose serial number is not known to `serial_numbers` are reported as
+    /// [unchecked](Balance::unchecked), as their spent status cannot be checked.
+    ///
+    /// # Arguments
+    /// * `view_key` - The view key of the address
+    /// * `serial_numbers` - The source of the serial numbers of the records of the address
+    /// * `block_heights` - The range of block heights to scan for private records
+    pub fn get_balance_of(
+        &self,
+        view_key: &ViewKey,
+        serial_numbers: &dyn SerialNumberSource,
+        block_heights: Range<u32>,
+    ) -> Result<Balance> {
+        let latest_height = self.get_latest_block_height()?;
+        let (private, pending, unchecked) = self.get_private_balance(
+            view_key,
+            serial_numbers,
+            block_heights,
+            latest_height.saturating_sub(PENDING_CONFIRMATIONS),
+        )?;
+        Ok(Balance {
+            private,
+            pending,
+            unchecked,
+            ..self.get_mapping_balance_of(&view_key.to_address())?
+        })
+    }
+
+    // Fetches the public, bonded and unbonding amounts of an address from the credits mappings
+    fn get_mapping_balance_of(&self, address: &Address) -> Result<Balance> {
+        let bond = self.get_credits_mapping_value("bonded", address)?;
+        let unbond = self.get_credits_mapping_value("unbonding", address)?;
+        Ok(Balance {
+            public: self.get_public_balance_of(address)?,
+            bonded: bond
+                .as_ref()
+                .and_then(|bond| member_u64(bond, "microcredits"))
+                .unwrap_or_default(),
+            unbonding: unbond
+                .as_ref()
+                .and_then(|unbond| member_u64(unbond, "microcredits"))
+                .unwrap_or_default(),
+            unbonding_height: unbond
+                .as_ref()
+                .and_then(|unbond| member_u32(unbond, "height")),
+            ..Balance::default()
+        })
+    }
+
+    // Sums the unspent credits records, split between those created up to `pending_height` and
+    // those created after it, and sums the records whose serial number is unknown
+    fn get_private_balance(
+        &self,
+        view_key: &ViewKey,
+        serial_numbers: &dyn SerialNumberSource,
+        block_heights: Range<u32>,
+        pending_height: u32,
+    ) -> Result<(u64, u64, u64)> {
+        let credits = ProgramID::from_str("credits.aleo")?;
+        let (mut private, mut pending, mut unchecked) = (0, 0, 0);
+        for start_height in block_heights.clone().step_by(BATCH_SIZE as usize) {
+            let end_height = block_heights.end.min(start_height + BATCH_SIZE);
+            for block in self.get_blocks_in_range(start_height, end_height)? {
+                for transition in block.transitions() {
+                    if transition.program_id() != &credits {
+                        continue;
+                    }
+                    for (commitment, record) in transition.records() {
+                        if !record.is_owner(view_key) {
+                            continue;
+                        }
+                        let amount = record.decrypt(view_key)?.microcredits()?;
+                        let Ok(serial_number) = serial_numbers.serial_number(*commitment) else {
+                            unchecked += amount;
+                            continue;
+                        };
+                        if self.is_spent(serial_number)? {
+                            continue;
+                        }
+                        if block.height() > pending_height {
+                            pending += amount;
+                        } else {
+                            private += amount;
+                        }
+                    }
+                }
+            }
+        }
+        Ok((private, pending, unchecked))
+    }
+
+    // Fetches the value of a `credits.aleo` mapping at an address
+    fn get_credits_mapping_value(&self, mapping: &str, address: &Address) -> Result<Option<Value>> {
+        self.get_json(&format!(
+            "/program/credits.aleo/mapping/{mapping}/{address}"
+        ))
+    }
+}
+
+// Formats microcredits as credits, with the 6 decimals of microcredits
+pub(crate) fn credits(amount: u64) -> String {
+    format!(
+        "{}.{:06} credits",
+        amount / MICROCREDITS,
+        amount % MICROCREDITS
+    )
+}
+
+// Reads a member of a struct value
+fn member(value: &Value, name: &str) -> Option<Plaintext> {
+    match value {
+        Value::Plaintext(plaintext) => plaintext.find(&[Identifier::from_str(name).ok()?]).ok(),
+        _ => None,
+    }
+}
+
+fn member_u64(value: &Value, name: &str) -> Option<u64> {
+    match member(value, name)? {
+        Plaintext::Literal(Literal::U64(amount), _) => Some(*amount),
+        _ => None,
+    }
+}
+
+fn member_u32(value: &Value, name: &str) -> Option<u32> {
+    match member(value, name)? {
+        Plaintext::Literal(Literal::U32(height), _) => Some(*height),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::backend::MemoryLedger;
+
+    #[test]
+    fn test_mapping_members() {
+        let unbond = Value::from_str("{ microcredits: 5000000u64, height: 360u32 }").unwrap();
+        assert_eq!(member_u64(&unbond, "microcredits"), Some(5_000_000));
+        assert_eq!(member_u32(&unbond, "height"), Some(360));
+        assert_eq!(member_u64(&unbond, "height"), None);
+        assert_eq!(member_u64(&unbond, "validator"), None);
+    }
+
+    #[test]
+    fn test_balance() {
+        let balance = Balance {
+            public: 1_500_000,
+            private: 2_000_000,
+            pending: 10,
+            unchecked: 0,
+            bonded: 10_000_000,
+            unbonding: 0,
+            unbonding_height: None,
+        };
+        assert_eq!(balance.spendable(), 3_500_000);
+        assert_eq!(balance.total(), 13_500_010);
+        assert!(balance
+            .to_string()
+            .starts_with("Public:    1.500000 credits\n"));
+        assert!(!balance.to_string().contains("Unchecked"));
+
+        let balance = Balance {
+            unchecked: 1_000,
+            ..balance
+        };
+        assert_eq!(balance.total(), 13_500_010);
+        assert!(balance
+            .to_string()
+            .ends_with("\nUnchecked: 0.001000 credits (spent status unknown)"));
+    }
+
+    #[test]
+    fn test_is_spent() {
+        let ledger = Arc::new(MemoryLedger::new(0).unwrap());
+        ledger.fund(&Address::zero(), MICROCREDITS).unwrap();
+        let agent = Agent::builder().with_backend(ledger).build();
+        let block = agent.get_block_of_height(1).unwrap();
+        let transition = block.transitions().next().unwrap();
+        let input_id = *transition.inputs()[0].id();
+        assert!(agent.is_spent(input_id).unwrap());
+        assert!(!agent.is_spent(Field::from_u64(1)).unwrap());
+
+        // A node failing to answer does not make records unspent
+        let unreachable = Agent::builder().with_url("http://127.0.0.1:1").build();
+        assert!(unreachable.is_spent(Field::from_u64(1)).is_err());
+    }
+}
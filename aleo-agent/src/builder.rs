@@ -2,13 +2,21 @@
 
 use crate::account::Account;
 use crate::agent::Agent;
+use crate::backend::Backend;
+use crate::signer::Signer;
+use crate::wallet::Wallet;
 use crate::{DEFAULT_BASE_URL, DEFAULT_TESTNET};
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AgentBuilder {
     url: String,
     network: String,
     account: Account,
+    wallet: Option<Wallet>,
+    signer: Option<Arc<dyn Signer>>,
+    backend: Option<Arc<dyn Backend>>,
+    rng_seed: Option<u64>,
 }
 
 impl Default for AgentBuilder {
@@ -17,13 +25,30 @@ impl Default for AgentBuilder {
             url: DEFAULT_BASE_URL.to_string(),
             network: DEFAULT_TESTNET.to_string(),
             account: Account::default(),
+            wallet: None,
+            signer: None,
+            backend: None,
+            rng_seed: None,
         }
     }
 }
 
 impl AgentBuilder {
     pub fn build(self) -> Agent {
-        Agent::new(self.url, self.network, self.account)
+        let mut agent = Agent::new(self.url, self.network, self.account);
+        if let Some(wallet) = self.wallet {
+            agent.set_wallet(wallet);
+        }
+        if let Some(signer) = self.signer {
+            agent.set_external_signer(signer);
+        }
+        if let Some(backend) = self.backend {
+            agent.set_backend(backend);
+        }
+        if let Some(seed) = self.rng_seed {
+            agent.set_rng_seed(seed);
+        }
+        agent
     }
 
     pub fn with_url<S: Into<String>>(mut self, url: S) -> Self {
@@ -40,4 +65,32 @@ impl AgentBuilder {
         self.account = account;
         self
     }
+
+    /// Uses a wallet of named accounts, signing with its default account.
+    pub fn with_wallet(mut self, wallet: Wallet) -> Self {
+        if let Some(account) = wallet.default_account() {
+            self.account = account.clone();
+        }
+        self.wallet = Some(wallet);
+        self
+    }
+
+    /// Signs with an external signer, such as a remote key custodian, see [Signer].
+    pub fn with_external_signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Talks to a backend, such as an in-process ledger, instead of a node over HTTP, see [Backend].
+    pub fn with_backend(mut self, backend: Arc<dyn Backend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Draws the randomness of transactions and signatures from a stream seeded with `seed`,
+    /// instead of the thread RNG, so that runs can be reproduced, see [crate::rng].
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
 }
@@ -1,7 +1,11 @@
 //! Node APIs
-use anyhow::{bail, Result};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
 
 use crate::agent::Agent;
+use crate::backend::is_not_found;
 
 use super::*;
 
@@ -12,8 +16,7 @@ impl Agent {
     /// # Returns
     /// The `Ok` variant wraps the latest block height as `u32`.
     pub fn get_latest_block_height(&self) -> Result<u32> {
-        let url = format!("{}/{}/block/height/latest", self.base_url(), self.network());
-        match self.client().get(&url).call()?.into_json() {
+        match self.get_json("/block/height/latest") {
             Ok(height) => Ok(height),
             Err(error) => bail!("Failed to parse the latest block height: {error}"),
         }
@@ -24,8 +27,7 @@ impl Agent {
     /// # Returns
     /// The `Ok` variant wraps the latest block hash as `BlockHash`.
     pub fn get_latest_block_hash(&self) -> Result<BlockHash> {
-        let url = format!("{}/{}/block/hash/latest", self.base_url(), self.network());
-        match self.client().get(&url).call()?.into_json() {
+        match self.get_json("/block/hash/latest") {
             Ok(hash) => Ok(hash),
             Err(error) => bail!("Failed to parse the latest block hash: {error}"),
         }
@@ -36,8 +38,7 @@ impl Agent {
     /// # Returns
     /// The `Ok` variant wraps the latest block as `Block`.
     pub fn get_latest_block(&self) -> Result<Block> {
-        let url = format!("{}/{}/latest/block/height", self.base_url(), self.network());
-        match self.client().get(&url).call()?.into_json() {
+        match self.get_json("/block/latest") {
             Ok(block) => Ok(block),
             Err(error) => bail!("Failed to parse the latest block: {error}"),
         }
@@ -51,13 +52,25 @@ impl Agent {
     /// # Returns
     /// The `Ok` variant wraps the block of the specific height as `Block`.
     pub fn get_block_of_height(&self, height: u32) -> Result<Block> {
-        let url = format!("{}/{}/block/{height}", self.base_url(), self.network());
-        match self.client().get(&url).call()?.into_json() {
+        let path = format!("/block/{height}");
+        match self.get_json(&path) {
             Ok(block) => Ok(block),
             Err(error) => bail!("Failed to parse block {height}: {error}"),
         }
     }
 
+    /// Retrieves a deployed program from the network.
+    ///
+    /// # Arguments
+    /// * `program_id` - The ID of the program.
+    pub fn get_program(&self, program_id: &ProgramID) -> Result<Program> {
+        let path = format!("/program/{program_id}");
+        match self.get_json(&path) {
+            Ok(program) => Ok(program),
+            Err(error) => bail!("Failed to parse program {program_id}: {error}"),
+        }
+    }
+
     /// Retrieves the transactions of a block of a specific height from the network.
     ///
     /// # Arguments
@@ -66,12 +79,8 @@ impl Agent {
     /// # Returns
     /// The `Ok` variant wraps the transactions of the block of the specific height as `Transactions`.
     pub fn get_transactions_of_height(&self, height: u32) -> Result<Transactions> {
-        let url = format!(
-            "{}/{}/block/{height}/transactions",
-            self.base_url(),
-            self.network()
-        );
-        match self.client().get(&url).call()?.into_json() {
+        let path = format!("/block/{height}/transactions");
+        match self.get_json(&path) {
             Ok(block) => Ok(block),
             Err(error) => bail!("Failed to parse block {height}: {error}"),
         }
@@ -95,12 +104,8 @@ impl Agent {
             bail!("The range of blocks must be less than 50");
         }
 
-        let url = format!(
-            "{}/{}/blocks?start={start_height}&end={end_height}",
-            self.base_url(),
-            self.network()
-        );
-        match self.client().get(&url).call()?.into_json() {
+        let path = format!("/blocks?start={start_height}&end={end_height}");
+        match self.get_json(&path) {
             Ok(blocks) => Ok(blocks),
             Err(error) => {
                 bail!("Failed to parse blocks {start_height} (inclusive) to {end_height} (exclusive): {error}")
@@ -116,13 +121,8 @@ impl Agent {
     /// # Returns
     /// The `Ok` variant wraps the transaction as `Transaction`.
     pub fn get_transaction(&self, transaction_id: &str) -> Result<Transaction> {
-        let url = format!(
-            "{}/{}/transaction/{}",
-            self.base_url(),
-            self.network(),
-            transaction_id
-        ).replace('"', "");
-        match self.client().get(&url).call()?.into_json() {
+        let path = format!("/transaction/{}", transaction_id).replace('"', "");
+        match self.get_json(&path) {
             Ok(transaction) => Ok(transaction),
             Err(error) => bail!("Failed to parse transaction '{transaction_id}': {error}"),
         }
@@ -136,18 +136,38 @@ impl Agent {
     /// # Returns
     /// The `Ok` variant wraps the confirmed transaction as `ConfirmedTransaction`.
     pub fn get_confirmed_transaction(&self, transaction_id: &str) -> Result<ConfirmedTransaction> {
-        let url = format!(
-            "{}/{}/transaction/confirmed/{}",
-            self.base_url(),
-            self.network(),
-            transaction_id
-        ).replace('"', "");
-        match self.client().get(&url).call()?.into_json() {
+        let path = format!("/transaction/confirmed/{}", transaction_id).replace('"', "");
+        match self.get_json(&path) {
             Ok(transaction) => Ok(transaction),
             Err(error) => bail!("Failed to parse transaction '{transaction_id}': {error}"),
         }
     }
 
+    /// Waits until a transaction is confirmed on the network.
+    ///
+    /// # Arguments
+    /// * `transaction_id` - The id of the transaction to wait for.
+    /// * `timeout` - The maximum time to wait for.
+    ///
+    /// # Returns
+    /// The `Ok` variant wraps the confirmed transaction as `ConfirmedTransaction`.
+    pub fn wait_for_confirmation(
+        &self,
+        transaction_id: &str,
+        timeout: Duration,
+    ) -> Result<ConfirmedTransaction> {
+        let start = Instant::now();
+        loop {
+            if let Ok(transaction) = self.get_confirmed_transaction(transaction_id) {
+                return Ok(transaction);
+            }
+            if start.elapsed() >= timeout {
+                bail!("Transaction '{transaction_id}' was not confirmed after {timeout:?}");
+            }
+            sleep(Duration::from_secs(5));
+        }
+    }
+
     /// Retrieves the pending transactions currently in the mempool from the network.
     ///
     /// # Returns
@@ -172,6 +192,9 @@ impl Agent {
     /// # Returns
     /// The `Ok` variant wraps the Transaction ID from the network as a `String`.
     pub fn broadcast_transaction(&self, transaction: &Transaction) -> Result<String> {
+        if let Some(backend) = self.backend() {
+            return backend.broadcast(transaction);
+        }
         let url = format!(
             "{}/{}/transaction/broadcast",
             self.base_url(),
@@ -226,18 +249,28 @@ impl Agent {
         &self,
         transaction_id: &TransactionID,
     ) -> Result<BlockHash> {
-        let url = format!(
-            "{}/{}/find/blockHash/{}",
-            self.base_url(),
-            self.network(),
-            transaction_id
-        ).replace('"', "");
-        match self.client().get(&url).call()?.into_json() {
+        let path = format!("/find/blockHash/{}", transaction_id).replace('"', "");
+        match self.get_json(&path) {
             Ok(hash) => Ok(hash),
             Err(error) => bail!("Failed to parse block hash: {error}"),
         }
     }
 
+    /// Returns the ID of the transaction that deployed the given program.
+    ///
+    /// # Arguments
+    /// * `program_id` - The id of the deployed program.
+    ///
+    /// # Returns
+    /// The `Ok` variant wraps the transaction ID as `TransactionID`.
+    pub fn find_deployment_transaction_id(&self, program_id: &ProgramID) -> Result<TransactionID> {
+        let path = format!("/find/transactionID/deployment/{program_id}");
+        match self.get_json(&path) {
+            Ok(transaction_id) => Ok(transaction_id),
+            Err(error) => bail!("Failed to parse deployment transaction ID of {program_id}: {error}"),
+        }
+    }
+
     /// Retrieves the transition ID that contains the given `input ID` or `output ID` from the network.
     ///
     /// # Arguments
@@ -249,14 +282,21 @@ impl Agent {
         &self,
         input_or_output_id: Field,
     ) -> Result<TransitionID> {
-        let url = format!(
-            "{}/{}/find/transitionID/{input_or_output_id}",
-            self.base_url(),
-            self.network()
-        );
-        match self.client().get(&url).call()?.into_json() {
-            Ok(transition_id) => Ok(transition_id),
-            Err(error) => bail!("Failed to parse transition ID: {error}"),
+        let path = format!("/find/transitionID/{input_or_output_id}");
+        // The error is kept, for callers to tell an unknown ID apart with `is_not_found`
+        self.get_json(&path).context("Failed to find the transition ID")
+    }
+
+    /// Returns `true` if the record with the given serial number is spent.
+    ///
+    /// # Errors
+    /// If the node fails to answer whether the serial number is known: a record is only reported
+    /// unspent when the node answers that its serial number was never published.
+    pub fn is_spent(&self, serial_number: Field) -> Result<bool> {
+        match self.find_transition_id_by_input_or_output_id(serial_number) {
+            Ok(_) => Ok(true),
+            Err(error) if is_not_found(&error) => Ok(false),
+            Err(error) => Err(error),
         }
     }
 }
@@ -0,0 +1,729 @@
+//! Generation of typed Rust clients from Aleo program source
+//!
+//! The generator reads a [Program] and emits a Rust module containing:
+//! * one struct per program `struct`, convertible to and from [Plaintext]
+//! * one wrapper per program `record`, with typed getters for its entries
+//! * a `<Name>Client` wrapping [ProgramManager], with one method per function taking typed inputs,
+//!   one `decode_<function>_outputs` helper per function decoding the outputs of an
+//!   [ExecutionReceipt](crate::receipt::ExecutionReceipt) and one typed getter per mapping. Its
+//!   constructor is provided by [ProgramClient], so that it does not collide with the functions
+//!
+//! Identifiers that are keywords in Rust are escaped, `type` becomes `r#type` and `crate`, which
+//! cannot be a raw identifier, becomes `crate_`.
+//!
+//! The emitted code only depends on `aleo-agent` and `anyhow`.
+//!
+//! # Example
+//! Generating a client from a `build.rs` script:
+//! ```ignore
+//! use aleo_agent::codegen;
+//! use aleo_agent::program::ProgramManager;
+//!
+//! fn main() {
+//!     let program = ProgramManager::load_program_from_path("contract/build").unwrap();
+//!     let out_dir = std::env::var("OUT_DIR").unwrap();
+//!     codegen::write_client(&program, format!("{out_dir}/client.rs")).unwrap();
+//! }
+//! ```
+//! The generated file is then included in the crate with
+//! `include!(concat!(env!("OUT_DIR"), "/client.rs"));`, and the client is created with
+//! `<TokenClient as ProgramClient>::new(&agent)`.
+//!
+//! The emitted code names every type by its full path, so that a program declaring a struct or a
+//! record called `agent`, `plaintext_record` or `vec` does not clash with the types of `aleo-agent`.
+
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use once_cell::sync::OnceCell;
+use snarkvm::console::program::{LiteralType, PlaintextType, ValueType};
+use snarkvm::console::types;
+
+use crate::agent::Agent;
+use crate::program::ProgramManager;
+
+use super::*;
+
+/// The constructor and accessors of a generated client.
+///
+/// They are not inherent methods of the client, which are the functions of the program: a function
+/// named `new` is called with `client.new(..)`, the constructor with `ProgramClient::new(&agent)`.
+pub trait ProgramClient<'agent>: Sized {
+    /// The ID of the program.
+    const PROGRAM_ID: &'static str;
+
+    /// Creates a client of the program.
+    fn new(agent: &'agent Agent) -> Result<Self>;
+
+    /// Returns the program manager used by the client.
+    fn program_manager(&self) -> &ProgramManager<'agent>;
+}
+
+/// A Rust type that can be converted to and from an Aleo [Plaintext].
+///
+/// Implemented for the primitive literal types, for `Vec<T>` (Aleo arrays) and for every struct
+/// emitted by the generator.
+pub trait AleoType: Sized {
+    /// Converts the value into its plaintext representation.
+    fn to_plaintext(&self) -> Result<Plaintext>;
+
+    /// Parses the value from its plaintext representation.
+    fn from_plaintext(plaintext: &Plaintext) -> Result<Self>;
+}
+
+macro_rules! impl_aleo_integer {
+    ($($ty:ty => $variant:ident),* $(,)?) => {$(
+        impl AleoType for $ty {
+            fn to_plaintext(&self) -> Result<Plaintext> {
+                Ok(Plaintext::from(Literal::$variant(types::$variant::new(*self))))
+            }
+
+            fn from_plaintext(plaintext: &Plaintext) -> Result<Self> {
+                match plaintext {
+                    Plaintext::Literal(Literal::$variant(value), _) => Ok(**value),
+                    _ => bail!("Expected a {} literal, found '{plaintext}'", stringify!($ty)),
+                }
+            }
+        }
+    )*};
+}
+
+impl_aleo_integer!(
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, i128 => I128,
+    u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128,
+);
+
+macro_rules! impl_aleo_literal {
+    ($($ty:ty => $variant:ident),* $(,)?) => {$(
+        impl AleoType for $ty {
+            fn to_plaintext(&self) -> Result<Plaintext> {
+                Ok(Plaintext::from(Literal::$variant(*self)))
+            }
+
+            fn from_plaintext(plaintext: &Plaintext) -> Result<Self> {
+                match plaintext {
+                    Plaintext::Literal(Literal::$variant(value), _) => Ok(*value),
+                    _ => bail!("Expected a {} literal, found '{plaintext}'", stringify!($variant)),
+                }
+            }
+        }
+    )*};
+}
+
+impl_aleo_literal!(Address => Address, Field => Field, Group => Group, Scalar => Scalar);
+
+impl AleoType for bool {
+    fn to_plaintext(&self) -> Result<Plaintext> {
+        Ok(Plaintext::from(Literal::Boolean(types::Boolean::new(
+            *self,
+        ))))
+    }
+
+    fn from_plaintext(plaintext: &Plaintext) -> Result<Self> {
+        match plaintext {
+            Plaintext::Literal(Literal::Boolean(value), _) => Ok(**value),
+            _ => bail!("Expected a boolean literal, found '{plaintext}'"),
+        }
+    }
+}
+
+impl AleoType for Signature {
+    fn to_plaintext(&self) -> Result<Plaintext> {
+        Ok(Plaintext::from(Literal::Signature(Box::new(*self))))
+    }
+
+    fn from_plaintext(plaintext: &Plaintext) -> Result<Self> {
+        match plaintext {
+            Plaintext::Literal(Literal::Signature(value), _) => Ok(**value),
+            _ => bail!("Expected a signature literal, found '{plaintext}'"),
+        }
+    }
+}
+
+impl AleoType for Plaintext {
+    fn to_plaintext(&self) -> Result<Plaintext> {
+        Ok(self.clone())
+    }
+
+    fn from_plaintext(plaintext: &Plaintext) -> Result<Self> {
+        Ok(plaintext.clone())
+    }
+}
+
+impl<T: AleoType> AleoType for Vec<T> {
+    fn to_plaintext(&self) -> Result<Plaintext> {
+        let elements = self
+            .iter()
+            .map(AleoType::to_plaintext)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Plaintext::Array(elements, OnceCell::new()))
+    }
+
+    fn from_plaintext(plaintext: &Plaintext) -> Result<Self> {
+        match plaintext {
+            Plaintext::Array(elements, _) => elements.iter().map(T::from_plaintext).collect(),
+            _ => bail!("Expected an array, found '{plaintext}'"),
+        }
+    }
+}
+
+/// Builds a struct plaintext from its members, in declaration order.
+pub fn struct_plaintext(members: Vec<(&str, Plaintext)>) -> Result<Plaintext> {
+    let members = members
+        .into_iter()
+        .map(|(name, value)| Ok((Identifier::from_str(name)?, value)))
+        .collect::<Result<IndexMap<_, _>>>()?;
+    Ok(Plaintext::Struct(members, OnceCell::new()))
+}
+
+/// Returns the member `name` of a struct plaintext.
+pub fn struct_member<'a>(plaintext: &'a Plaintext, name: &str) -> Result<&'a Plaintext> {
+    match plaintext {
+        Plaintext::Struct(members, _) => match members.get(&Identifier::from_str(name)?) {
+            Some(member) => Ok(member),
+            None => bail!("Struct member '{name}' is missing in '{plaintext}'"),
+        },
+        _ => bail!("Expected a struct, found '{plaintext}'"),
+    }
+}
+
+/// Returns the entry `name` of a record, converted to `T`.
+pub fn record_entry<T: AleoType>(record: &PlaintextRecord, name: &str) -> Result<T> {
+    match record.data().get(&Identifier::from_str(name)?) {
+        Some(Entry::Constant(plaintext))
+        | Some(Entry::Public(plaintext))
+        | Some(Entry::Private(plaintext)) => T::from_plaintext(plaintext),
+        None => bail!("Record entry '{name}' is missing"),
+    }
+}
+
+/// Converts a plaintext output value to `T`.
+pub fn decode_output<T: AleoType>(value: &Value) -> Result<T> {
+    match value {
+        Value::Plaintext(plaintext) => T::from_plaintext(plaintext),
+        _ => bail!("Expected a plaintext output, found '{value}'"),
+    }
+}
+
+/// Extracts the record of a record output value.
+pub fn decode_record(value: &Value) -> Result<PlaintextRecord> {
+    match value {
+        Value::Record(record) => Ok(record.clone()),
+        _ => bail!("Expected a record output, found '{value}'"),
+    }
+}
+
+/// Generates the source of a typed Rust client module for the given program.
+pub fn generate_client(program: &Program) -> Result<String> {
+    let mut out = String::new();
+    let program_name = program.id().name().to_string();
+    let client_name = format!("{}Client", camel_case(&program_name));
+
+    writeln!(
+        out,
+        "// Generated by aleo-agent from `{}`. Do not edit.",
+        program.id()
+    )?;
+    writeln!(out)?;
+    // Paths are qualified so that the program's own structs and records cannot clash with them
+    writeln!(out, "#[allow(unused_imports)]")?;
+    writeln!(out, "use aleo_agent::codegen::AleoType as _;")?;
+    writeln!(out)?;
+
+    // Structs
+    for (name, struct_type) in program.structs() {
+        let rust_name = camel_case(&name.to_string());
+        writeln!(out, "#[derive(Clone, Debug)]")?;
+        writeln!(out, "pub struct {rust_name} {{")?;
+        for (member, member_type) in struct_type.members() {
+            writeln!(
+                out,
+                "    pub {}: {},",
+                rust_ident(&member.to_string()),
+                rust_type(member_type)
+            )?;
+        }
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(out, "impl aleo_agent::codegen::AleoType for {rust_name} {{")?;
+        writeln!(
+            out,
+            "    fn to_plaintext(&self) -> anyhow::Result<aleo_agent::Plaintext> {{"
+        )?;
+        writeln!(
+            out,
+            "        aleo_agent::codegen::struct_plaintext(::std::vec!["
+        )?;
+        for (member, _) in struct_type.members() {
+            writeln!(
+                out,
+                "            (\"{member}\", self.{}.to_plaintext()?),",
+                rust_ident(&member.to_string())
+            )?;
+        }
+        writeln!(out, "        ])")?;
+        writeln!(out, "    }}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    fn from_plaintext(plaintext: &aleo_agent::Plaintext) -> anyhow::Result<Self> {{"
+        )?;
+        writeln!(out, "        Ok(Self {{")?;
+        for (member, _) in struct_type.members() {
+            writeln!(
+                out,
+                "            {}: aleo_agent::codegen::AleoType::from_plaintext(aleo_agent::codegen::struct_member(plaintext, \"{member}\")?)?,",
+                rust_ident(&member.to_string())
+            )?;
+        }
+        writeln!(out, "        }})")?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+    }
+
+    // Records
+    for (name, record_type) in program.records() {
+        let rust_name = camel_case(&name.to_string());
+        writeln!(out, "#[derive(Clone, Debug)]")?;
+        writeln!(out, "pub struct {rust_name} {{")?;
+        writeln!(out, "    pub record: aleo_agent::PlaintextRecord,")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(out, "#[allow(clippy::all)]")?;
+        writeln!(out, "impl {rust_name} {{")?;
+        writeln!(out, "    pub fn owner(&self) -> aleo_agent::Address {{")?;
+        writeln!(out, "        **self.record.owner()")?;
+        writeln!(out, "    }}")?;
+        for (entry, entry_type) in record_type.entries() {
+            let plaintext_type = match entry_type {
+                snarkvm::console::program::EntryType::Constant(plaintext_type)
+                | snarkvm::console::program::EntryType::Public(plaintext_type)
+                | snarkvm::console::program::EntryType::Private(plaintext_type) => plaintext_type,
+            };
+            writeln!(out)?;
+            writeln!(
+                out,
+                "    pub fn {}(&self) -> anyhow::Result<{}> {{",
+                rust_ident(&entry.to_string()),
+                rust_type(plaintext_type)
+            )?;
+            writeln!(
+                out,
+                "        aleo_agent::codegen::record_entry(&self.record, \"{entry}\")"
+            )?;
+            writeln!(out, "    }}")?;
+        }
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "impl ::std::convert::From<aleo_agent::PlaintextRecord> for {rust_name} {{"
+        )?;
+        writeln!(
+            out,
+            "    fn from(record: aleo_agent::PlaintextRecord) -> Self {{"
+        )?;
+        writeln!(out, "        Self {{ record }}")?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "impl ::std::convert::TryFrom<&aleo_agent::Value> for {rust_name} {{"
+        )?;
+        writeln!(out, "    type Error = anyhow::Error;")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    fn try_from(value: &aleo_agent::Value) -> anyhow::Result<Self> {{"
+        )?;
+        writeln!(
+            out,
+            "        aleo_agent::codegen::decode_record(value).map(Self::from)"
+        )?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+    }
+
+    // Client
+    writeln!(out, "#[derive(Clone)]")?;
+    writeln!(out, "pub struct {client_name}<'agent> {{")?;
+    writeln!(out, "    pm: aleo_agent::program::ProgramManager<'agent>,")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "impl<'agent> aleo_agent::codegen::ProgramClient<'agent> for {client_name}<'agent> {{"
+    )?;
+    writeln!(
+        out,
+        "    const PROGRAM_ID: &'static str = \"{}\";",
+        program.id()
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "    fn new(agent: &'agent aleo_agent::agent::Agent) -> anyhow::Result<Self> {{"
+    )?;
+    writeln!(
+        out,
+        "        Ok(Self {{ pm: agent.program(Self::PROGRAM_ID)? }})"
+    )?;
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "    fn program_manager(&self) -> &aleo_agent::program::ProgramManager<'agent> {{"
+    )?;
+    writeln!(out, "        &self.pm")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    // The methods are named after the functions of the program, such as `new`, and decode single
+    // outputs with `Ok(..?)`
+    writeln!(out, "#[allow(clippy::all)]")?;
+    writeln!(out, "impl<'agent> {client_name}<'agent> {{")?;
+
+    for (index, (name, function)) in program.functions().iter().enumerate() {
+        let input_types = function.input_types();
+        let output_types = function.output_types();
+
+        // Execution method
+        if index > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "    /// Executes `{}/{name}`.", program.id())?;
+        write!(out, "    pub fn {}(&self", rust_ident(&name.to_string()))?;
+        for (index, input_type) in input_types.iter().enumerate() {
+            write!(out, ", r{index}: {}", rust_value_type(input_type))?;
+        }
+        writeln!(
+            out,
+            ", priority_fee: u64, fee_record: ::std::option::Option<aleo_agent::PlaintextRecord>) -> anyhow::Result<aleo_agent::receipt::ExecutionReceipt> {{"
+        )?;
+        writeln!(
+            out,
+            "        let inputs: ::std::vec::Vec<aleo_agent::Value> = ::std::vec!["
+        )?;
+        for (index, input_type) in input_types.iter().enumerate() {
+            writeln!(out, "            {},", input_expression(index, input_type))?;
+        }
+        writeln!(out, "        ];")?;
+        writeln!(
+            out,
+            "        self.pm.execute_program(\"{name}\", inputs.into_iter(), priority_fee, fee_record)"
+        )?;
+        writeln!(out, "    }}")?;
+
+        // Output decoding
+        let decoded_types = output_types.iter().map(rust_value_type).collect::<Vec<_>>();
+        let return_type = match decoded_types.len() {
+            1 => decoded_types[0].clone(),
+            _ => format!("({})", decoded_types.join(", ")),
+        };
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    /// Decodes the outputs of `{}/{name}`, as returned by `ExecutionReceipt::outputs`.",
+            program.id()
+        )?;
+        writeln!(
+            out,
+            "    pub fn decode_{name}_outputs(outputs: &[aleo_agent::Value]) -> anyhow::Result<{return_type}> {{"
+        )?;
+        writeln!(out, "        if outputs.len() != {} {{", output_types.len())?;
+        writeln!(
+            out,
+            "            anyhow::bail!(\"Expected {} outputs for '{name}', found {{}}\", outputs.len());",
+            output_types.len()
+        )?;
+        writeln!(out, "        }}")?;
+        let decoded = output_types
+            .iter()
+            .enumerate()
+            .map(|(index, output_type)| output_expression(index, output_type))
+            .collect::<Vec<_>>();
+        match decoded.len() {
+            1 => writeln!(out, "        Ok({})", decoded[0])?,
+            _ => writeln!(out, "        Ok(({}))", decoded.join(", "))?,
+        }
+        writeln!(out, "    }}")?;
+    }
+
+    for (name, mapping) in program.mappings() {
+        let key_type = rust_type(mapping.key().plaintext_type());
+        let value_type = rust_type(mapping.value().plaintext_type());
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    /// Reads the current value of `{}/{name}[key]`.",
+            program.id()
+        )?;
+        writeln!(
+            out,
+            "    pub fn get_{name}(&self, key: &{key_type}) -> anyhow::Result<{value_type}> {{"
+        )?;
+        writeln!(
+            out,
+            "        let value = self.pm.get_mapping_value(<aleo_agent::Identifier as ::std::str::FromStr>::from_str(\"{name}\")?, key.to_plaintext()?)?;"
+        )?;
+        writeln!(out, "        aleo_agent::codegen::decode_output(&value)")?;
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "}}")?;
+
+    Ok(out)
+}
+
+/// Generates a typed Rust client for the given program and writes it to `path`.
+pub fn write_client<P: AsRef<Path>>(program: &Program, path: P) -> Result<()> {
+    let source = generate_client(program)?;
+    std::fs::write(path, source)?;
+    Ok(())
+}
+
+// Returns the Rust type used for an Aleo plaintext type
+fn rust_type(plaintext_type: &PlaintextType<CurrentNetwork>) -> String {
+    match plaintext_type {
+        PlaintextType::Literal(literal_type) => match literal_type {
+            LiteralType::Address => "aleo_agent::Address".to_string(),
+            LiteralType::Boolean => "bool".to_string(),
+            LiteralType::Field => "aleo_agent::Field".to_string(),
+            LiteralType::Group => "aleo_agent::Group".to_string(),
+            LiteralType::I8 => "i8".to_string(),
+            LiteralType::I16 => "i16".to_string(),
+            LiteralType::I32 => "i32".to_string(),
+            LiteralType::I64 => "i64".to_string(),
+            LiteralType::I128 => "i128".to_string(),
+            LiteralType::U8 => "u8".to_string(),
+            LiteralType::U16 => "u16".to_string(),
+            LiteralType::U32 => "u32".to_string(),
+            LiteralType::U64 => "u64".to_string(),
+            LiteralType::U128 => "u128".to_string(),
+            LiteralType::Scalar => "aleo_agent::Scalar".to_string(),
+            LiteralType::Signature => "aleo_agent::Signature".to_string(),
+            LiteralType::String => "aleo_agent::Plaintext".to_string(),
+        },
+        PlaintextType::Struct(name) => camel_case(&name.to_string()),
+        PlaintextType::Array(array_type) => {
+            format!(
+                "::std::vec::Vec<{}>",
+                rust_type(array_type.next_element_type())
+            )
+        }
+    }
+}
+
+// Returns the Rust type used for a function input or output
+fn rust_value_type(value_type: &ValueType<CurrentNetwork>) -> String {
+    match value_type {
+        ValueType::Constant(plaintext_type)
+        | ValueType::Public(plaintext_type)
+        | ValueType::Private(plaintext_type) => rust_type(plaintext_type),
+        ValueType::Record(name) => camel_case(&name.to_string()),
+        ValueType::ExternalRecord(_) => "aleo_agent::PlaintextRecord".to_string(),
+        ValueType::Future(_) => "aleo_agent::Value".to_string(),
+    }
+}
+
+// Returns the expression converting the input `r{index}` into a `Value`
+fn input_expression(index: usize, value_type: &ValueType<CurrentNetwork>) -> String {
+    match value_type {
+        ValueType::Record(_) => format!("aleo_agent::Value::Record(r{index}.record)"),
+        ValueType::ExternalRecord(_) => format!("aleo_agent::Value::Record(r{index})"),
+        ValueType::Future(_) => format!("r{index}"),
+        _ => format!("aleo_agent::Value::Plaintext(r{index}.to_plaintext()?)"),
+    }
+}
+
+// Returns the expression decoding `outputs[index]` into its Rust type
+fn output_expression(index: usize, value_type: &ValueType<CurrentNetwork>) -> String {
+    match value_type {
+        ValueType::Record(name) => {
+            format!(
+                "{}::try_from(&outputs[{index}])?",
+                camel_case(&name.to_string())
+            )
+        }
+        ValueType::ExternalRecord(_) => {
+            format!("aleo_agent::codegen::decode_record(&outputs[{index}])?")
+        }
+        ValueType::Future(_) => format!("outputs[{index}].clone()"),
+        _ => format!("aleo_agent::codegen::decode_output(&outputs[{index}])?"),
+    }
+}
+
+// Converts an Aleo identifier such as `token_info` into `TokenInfo`
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+// Escapes identifiers that are reserved in Rust, suffixing those that cannot be raw identifiers
+fn rust_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+        "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+        "try", "typeof", "unsized", "virtual", "yield",
+    ];
+    const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+    if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else if NON_RAW_KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TOKEN: &str = r"
+program token.aleo;
+
+struct token_info:
+    supply as u64;
+    admin as address;
+
+record token:
+    owner as address.private;
+    amount as u64.private;
+
+mapping balances:
+    key as address.public;
+    value as u64.public;
+
+function mint:
+    input r0 as address.private;
+    input r1 as u64.private;
+    cast r0 r1 into r2 as token.record;
+    output r2 as token.record;
+
+function transfer:
+    input r0 as token.record;
+    input r1 as address.private;
+    input r2 as u64.private;
+    sub r0.amount r2 into r3;
+    cast r1 r2 into r4 as token.record;
+    cast r0.owner r3 into r5 as token.record;
+    output r4 as token.record;
+    output r5 as token.record;
+";
+
+    #[test]
+    fn test_generate_client() {
+        let program = Program::from_str(TOKEN).unwrap();
+        let source = generate_client(&program).unwrap();
+        assert!(source.contains("pub struct TokenClient<'agent>"));
+        assert!(source.contains("pub struct TokenInfo {"));
+        assert!(source.contains("    pub supply: u64,"));
+        assert!(source.contains("pub fn amount(&self) -> anyhow::Result<u64>"));
+        assert!(source.contains(
+            "pub fn mint(&self, r0: aleo_agent::Address, r1: u64, priority_fee: u64, fee_record: ::std::option::Option<aleo_agent::PlaintextRecord>)"
+        ));
+        assert!(source.contains(
+            "pub fn decode_transfer_outputs(outputs: &[aleo_agent::Value]) -> anyhow::Result<(Token, Token)>"
+        ));
+        assert!(source.contains(
+            "pub fn get_balances(&self, key: &aleo_agent::Address) -> anyhow::Result<u64>"
+        ));
+    }
+
+    #[test]
+    fn test_literal_round_trip() {
+        let plaintext = 42u32.to_plaintext().unwrap();
+        assert_eq!(plaintext.to_string(), "42u32");
+        assert_eq!(u32::from_plaintext(&plaintext).unwrap(), 42);
+        assert!(u64::from_plaintext(&plaintext).is_err());
+
+        let array = vec![true, false].to_plaintext().unwrap();
+        assert_eq!(
+            Vec::<bool>::from_plaintext(&array).unwrap(),
+            vec![true, false]
+        );
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(camel_case("token_info"), "TokenInfo");
+        assert_eq!(rust_ident("type"), "r#type");
+        assert_eq!(rust_ident("crate"), "crate_");
+        assert_eq!(rust_ident("super"), "super_");
+        assert_eq!(rust_ident("self"), "self_");
+        assert_eq!(rust_ident("Self"), "Self_");
+        assert_eq!(rust_ident("amount"), "amount");
+    }
+
+    #[test]
+    fn test_generated_client_parses() {
+        const NAMES: &str = r"
+program names.aleo;
+
+struct pair:
+    crate as u64;
+    super as u64;
+
+struct agent:
+    amount as u64;
+
+struct vec:
+    first as u64;
+
+record plaintext_record:
+    owner as address.private;
+    amount as u64.private;
+
+function new:
+    input r0 as pair.private;
+    add r0.crate r0.super into r1;
+    output r1 as u64.private;
+
+function swap:
+    input r0 as agent.private;
+    input r1 as vec.private;
+    input r2 as plaintext_record.record;
+    output r0 as agent.private;
+    output r2 as plaintext_record.record;
+
+function program_manager:
+    input r0 as u64.public;
+    output r0 as u64.public;
+";
+        for source in [TOKEN, NAMES] {
+            let client = generate_client(&Program::from_str(source).unwrap()).unwrap();
+            if let Err(error) = syn::parse_file(&client) {
+                panic!("Invalid client for {source}: {error}\n{client}");
+            }
+        }
+
+        let client = generate_client(&Program::from_str(NAMES).unwrap()).unwrap();
+        assert!(client.contains("    pub crate_: u64,"));
+        assert!(client.contains("    pub fn new(&self, r0: Pair,"));
+        assert!(client.contains("pub struct Agent {"));
+        assert!(client.contains("pub struct Vec {"));
+        assert!(client.contains("pub struct PlaintextRecord {"));
+        assert!(client.contains(
+            "pub fn decode_swap_outputs(outputs: &[aleo_agent::Value]) -> anyhow::Result<(Agent, PlaintextRecord)>"
+        ));
+        assert!(client.contains(
+            "impl<'agent> aleo_agent::codegen::ProgramClient<'agent> for NamesClient<'agent> {"
+        ));
+    }
+}
@@ -0,0 +1,421 @@
+//! In-process validation of Aleo instructions
+//!
+//! Programs written in Aleo instructions can be parsed and type-checked with snarkVM directly,
+//! without running `leo build`. Errors are reported as [Diagnostic]s pointing at the offending line.
+//! A directory of programs is checked with [Agent::compile_dir], which fetches the imports missing
+//! from the directory from the agent's node.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+use snarkvm::package::Manifest;
+use snarkvm::prelude::Parser;
+
+use crate::agent::Agent;
+use crate::deploy::import_order;
+
+use super::*;
+
+/// A compile error located in a source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file containing the error, if the source was read from disk.
+    pub file: Option<PathBuf>,
+    /// The 1-based line of the error, if it could be located.
+    pub line: Option<usize>,
+    /// The 1-based column of the error, if it could be located.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(source: &str, offset: Option<usize>, message: String) -> Self {
+        let (line, column) = match offset {
+            Some(offset) => {
+                let (line, column) = line_and_column(source, offset);
+                (Some(line), Some(column))
+            }
+            None => (None, None),
+        };
+        Self {
+            file: None,
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn in_file(mut self, file: &Path) -> Self {
+        self.file = Some(file.to_path_buf());
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "{line}:")?;
+        }
+        if let Some(column) = self.column {
+            write!(f, "{column}:")?;
+        }
+        if self.file.is_some() || self.line.is_some() {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The diagnostics of a failed compilation.
+#[derive(Clone, Debug)]
+pub struct CompileError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, diagnostic) in self.diagnostics.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{diagnostic}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl From<Diagnostic> for CompileError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self {
+            diagnostics: vec![diagnostic],
+        }
+    }
+}
+
+/// Parses a program from Aleo instructions.
+///
+/// # Example
+/// ```
+/// use aleo_agent::compile::parse_program;
+///
+/// let error = parse_program("program bad.aleo;\n\nfunction main:\n    input r0 as u32.publik;\n").unwrap_err();
+/// assert!(error.diagnostics[0].line.is_some());
+/// println!("{error}");
+/// ```
+pub fn parse_program(source: &str) -> Result<Program, CompileError> {
+    match Program::parse(source) {
+        Ok((remainder, program)) => {
+            if remainder.trim().is_empty() {
+                return Ok(program);
+            }
+            // Parsing stops at the first item that is not valid
+            let offset = source.len() - remainder.len();
+            Err(Diagnostic::new(
+                source,
+                Some(offset),
+                format!("invalid program item '{}'", first_line(remainder)),
+            )
+            .into())
+        }
+        Err(nom::Err::Error(error)) | Err(nom::Err::Failure(error)) => {
+            // Report the error at the furthest position reached by the parser
+            let offset = error
+                .errors
+                .iter()
+                .map(|(input, _)| source.len() - input.len())
+                .max();
+            let message = match offset {
+                Some(offset) => format!("invalid syntax '{}'", first_line(&source[offset..])),
+                None => "invalid syntax".to_string(),
+            };
+            Err(Diagnostic::new(source, offset, message).into())
+        }
+        Err(nom::Err::Incomplete(_)) => Err(Diagnostic::new(
+            source,
+            Some(source.len()),
+            "unexpected end of program".to_string(),
+        )
+        .into()),
+    }
+}
+
+/// Type-checks a program with snarkVM.
+///
+/// # Arguments
+/// * `source` - The Aleo instructions of the program, used to locate errors
+/// * `program` - The parsed program
+/// * `imports` - The programs imported by `program`, in import order
+pub fn check_program(
+    source: &str,
+    program: &Program,
+    imports: &[Program],
+) -> Result<(), CompileError> {
+    let mut process =
+        Process::load().map_err(|error| Diagnostic::new(source, None, error.to_string()))?;
+    for import in imports {
+        add_import(&mut process, source, import)?;
+    }
+    add_checked(&mut process, source, program)?;
+    Ok(())
+}
+
+/// Parses and type-checks a program from Aleo instructions.
+///
+/// # Arguments
+/// * `source` - The Aleo instructions of the program
+/// * `imports` - The programs imported by the program, in import order
+pub fn compile_source(source: &str, imports: &[Program]) -> Result<Program, CompileError> {
+    let program = parse_program(source)?;
+    check_program(source, &program, imports)?;
+    Ok(program)
+}
+
+impl Agent {
+    /// Parses and type-checks a directory of Aleo instructions.
+    ///
+    /// The directory contains a `program.json` manifest naming the main program, and `.aleo` files
+    /// at its root or in `imports/`. The main program is found by its program ID among them,
+    /// whatever the name of its file, as are the programs it imports. Imports that are not found
+    /// locally are fetched from the agent's node, see [Agent::get_import_programs].
+    ///
+    /// # Example
+    /// ```ignore
+    /// use aleo_agent::agent::Agent;
+    ///
+    /// let agent = Agent::default();
+    /// match agent.compile_dir("contract/build") {
+    ///     Ok(program) => println!("{} is valid", program.id()),
+    ///     Err(error) => eprintln!("{error}"),
+    /// }
+    /// ```
+    pub fn compile_dir<P: AsRef<Path>>(&self, path: P) -> Result<Program, CompileError> {
+        let path = path.as_ref();
+        let manifest = Manifest::<CurrentNetwork>::open(path).map_err(|error| {
+            Diagnostic::new("", None, error.to_string()).in_file(&path.join("program.json"))
+        })?;
+
+        // Parse every program of the directory, reporting all syntax errors at once
+        let mut sources = IndexMap::new();
+        let mut diagnostics = vec![];
+        for file in
+            aleo_files(path).map_err(|error| Diagnostic::new("", None, error.to_string()))?
+        {
+            let source = fs::read_to_string(&file)
+                .map_err(|error| Diagnostic::new("", None, error.to_string()).in_file(&file))?;
+            match parse_program(&source) {
+                Ok(program) => {
+                    sources.insert(*program.id(), (file, source, program));
+                }
+                Err(error) => diagnostics.extend(
+                    error
+                        .diagnostics
+                        .into_iter()
+                        .map(|diagnostic| diagnostic.in_file(&file)),
+                ),
+            }
+        }
+        if !diagnostics.is_empty() {
+            return Err(CompileError { diagnostics });
+        }
+
+        let main_id = *manifest.program_id();
+        let Some((main_file, main_source, main_program)) = sources.get(&main_id).cloned() else {
+            return Err(
+                Diagnostic::new("", None, format!("program {main_id} was not found"))
+                    .in_file(&path.join("program.json"))
+                    .into(),
+            );
+        };
+
+        // Resolve imports that are not part of the directory from the agent's node
+        let mut programs = sources
+            .iter()
+            .map(|(id, (_, _, program))| (*id, program.clone()))
+            .collect::<IndexMap<_, _>>();
+        let missing = programs
+            .values()
+            .flat_map(|program| program.imports().keys().copied())
+            .filter(|id| !programs.contains_key(id))
+            .collect::<Vec<_>>();
+        for id in missing {
+            let resolved = self.get_program(&id).and_then(|program| {
+                let imports = self.get_import_programs(&program)?;
+                Ok((program, imports))
+            });
+            let (program, imports) = resolved.map_err(|error| {
+                Diagnostic::new(
+                    &main_source,
+                    None,
+                    format!("failed to resolve import {id}: {error}"),
+                )
+                .in_file(&main_file)
+            })?;
+            for (import_id, import) in imports {
+                programs.entry(import_id).or_insert(import);
+            }
+            programs.entry(id).or_insert(program);
+        }
+
+        // Check the programs in import order in a single process, so that every import is added before
+        // its importers
+        let order = import_order(&programs).map_err(|error| {
+            Diagnostic::new(&main_source, None, error.to_string()).in_file(&main_file)
+        })?;
+        let mut process = Process::load().map_err(|error| {
+            Diagnostic::new(&main_source, None, error.to_string()).in_file(&main_file)
+        })?;
+        for id in order {
+            let program = &programs[&id];
+            match sources.get(&id) {
+                Some((file, source, _)) => add_checked(&mut process, source, program)
+                    .map_err(|error| error.in_file(file))?,
+                // Programs fetched from the network are already deployed, and therefore valid
+                None => add_import(&mut process, &main_source, program)
+                    .map_err(|error| error.in_file(&main_file))?,
+            }
+        }
+        Ok(main_program)
+    }
+}
+
+// Adds an imported program to the process, `credits.aleo` being part of every process
+fn add_import(process: &mut Process, source: &str, import: &Program) -> Result<(), Diagnostic> {
+    let credits_id =
+        ProgramID::from_str("credits.aleo").expect("credits.aleo is a valid program ID");
+    if import.id() == &credits_id {
+        return Ok(());
+    }
+    process.add_program(import).map_err(|error| {
+        Diagnostic::new(
+            source,
+            None,
+            format!("imported program {} is invalid: {error}", import.id()),
+        )
+    })
+}
+
+// Adds a program to the process, locating its type errors in its source
+fn add_checked(process: &mut Process, source: &str, program: &Program) -> Result<(), Diagnostic> {
+    process.add_program(program).map_err(|error| {
+        let message = error.to_string();
+        Diagnostic::new(source, locate_item(source, program, &message), message)
+    })
+}
+
+// Lists the `.aleo` files of a directory and of its `imports/` subdirectory
+fn aleo_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for dir in [path.to_path_buf(), path.join("imports")] {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let file = entry?.path();
+            if file.extension().and_then(|extension| extension.to_str()) == Some("aleo") {
+                files.push(file);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// Finds the declaration of the program item named in an error message
+fn locate_item(source: &str, program: &Program, message: &str) -> Option<usize> {
+    let names = program
+        .functions()
+        .keys()
+        .chain(program.closures().keys())
+        .chain(program.structs().keys())
+        .chain(program.records().keys())
+        .chain(program.mappings().keys())
+        .map(ToString::to_string)
+        .filter(|name| message.contains(&format!("'{name}'")));
+    for name in names {
+        for keyword in ["function", "closure", "struct", "record", "mapping"] {
+            if let Some(offset) = source.find(&format!("{keyword} {name}:")) {
+                return Some(offset);
+            }
+        }
+    }
+    None
+}
+
+// Returns the 1-based line and column of a byte offset
+fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|index| index + 1).unwrap_or(0) + 1;
+    (line, column)
+}
+
+fn first_line(source: &str) -> &str {
+    source
+        .trim_start()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_program() {
+        let source = "program compile_test.aleo;\n\nfunction main:\n    input r0 as u32.public;\n    add r0 r0 into r1;\n    output r1 as u32.private;\n";
+        assert!(parse_program(source).is_ok());
+
+        let invalid = source.replace("add r0 r0", "addd r0 r0");
+        let error = parse_program(&invalid).unwrap_err();
+        assert_eq!(error.diagnostics.len(), 1);
+        assert!(error.diagnostics[0].line.unwrap() >= 3);
+    }
+
+    #[test]
+    fn test_compile_dir() {
+        let dir = std::env::temp_dir().join(format!("compile_dir_test_{}", std::process::id()));
+        fs::create_dir_all(dir.join("imports")).unwrap();
+        fs::write(
+            dir.join("program.json"),
+            r#"{"program": "app_test.aleo", "version": "0.0.0", "description": "", "license": "MIT"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("imports/lib_test.aleo"),
+            "program lib_test.aleo;\n\nfunction double:\n    input r0 as u32.public;\n    add r0 r0 into r1;\n    output r1 as u32.public;\n",
+        )
+        .unwrap();
+        let app = "import lib_test.aleo;\nprogram app_test.aleo;\n\nfunction main:\n    input r0 as u32.public;\n    call lib_test.aleo/double r0 into r1;\n    output r1 as u32.public;\n";
+        fs::write(dir.join("app.aleo"), app).unwrap();
+
+        let agent = Agent::default();
+        let program = agent.compile_dir(&dir);
+        let invalid = app.replace("lib_test.aleo/double", "lib_test.aleo/triple");
+        fs::write(dir.join("app.aleo"), invalid).unwrap();
+        let error = agent.compile_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(program.unwrap().id().to_string(), "app_test.aleo");
+        let error = error.unwrap_err();
+        assert_eq!(error.diagnostics.len(), 1);
+        assert_eq!(error.diagnostics[0].file, Some(dir.join("app.aleo")));
+    }
+
+    #[test]
+    fn test_line_and_column() {
+        let source = "program a.aleo;\nfunction main:\n    input r0 as u32.public;";
+        assert_eq!(line_and_column(source, 0), (1, 1));
+        assert_eq!(line_and_column(source, 16), (2, 1));
+        assert_eq!(line_and_column(source, 35), (3, 5));
+    }
+}
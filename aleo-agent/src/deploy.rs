@@ -1,11 +1,17 @@
 //! program deployment implementation
 
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::{bail, ensure, Error};
+use indexmap::IndexMap;
 
 use crate::agent::Agent;
+use crate::diff::ProgramDiff;
 use crate::program::ProgramManager;
+use crate::rng::CryptoRngCore;
 
 use super::*;
 
@@ -19,78 +25,139 @@ impl Agent {
     ///
     /// # Returns
     /// * The transaction hash of the deployment transaction
+    ///
+    /// Programs can only be deployed once on the current network: if a different version of the
+    /// program is already on chain, the error lists the changes, see [ProgramDiff].
+    ///
+    /// The deployment is verified locally before it is broadcast, see [Agent::verify_transaction].
     pub fn deploy_program(
         &self,
         program: &Program,
         priority_fee: u64,
         fee_record: Option<PlaintextRecord>,
+    ) -> anyhow::Result<String> {
+        self.with_rng(|rng| self.deploy_program_with_rng(program, priority_fee, fee_record, rng))
+    }
+
+    /// Deploys a program, drawing the randomness of the transaction from `rng`.
+    ///
+    /// See [deploy_program](Agent::deploy_program) for the arguments.
+    pub fn deploy_program_with_rng(
+        &self,
+        program: &Program,
+        priority_fee: u64,
+        fee_record: Option<PlaintextRecord>,
+        rng: &mut dyn CryptoRngCore,
     ) -> anyhow::Result<String> {
         // Check if program is already deployed on chain, cancel deployment if so
         let program_id = program.id();
-        ensure!(
-            ProgramManager::get_program_from_chain(program_id).is_err(),
-            "❌ Program {:?} already deployed on chain, cancelling deployment",
-            program_id
-        );
+        if let Ok(onchain_program) = self.get_program(program_id) {
+            let diff = ProgramDiff::new(&onchain_program, program);
+            ensure!(
+                !diff.is_empty(),
+                "❌ Program {program_id} already deployed on chain, cancelling deployment"
+            );
+            bail!(
+                "❌ Program {program_id} already deployed on chain with a different source, and the network only accepts edition {} deployments, cancelling deployment\n{diff}",
+                CurrentNetwork::EDITION
+            );
+        }
 
         // If the program has imports, check if they are deployed on chain. If not, cancel deployment
         program.imports().keys().try_for_each(|program_id| {
-            if ProgramManager::get_program_from_chain(program_id).is_err() {
+            if self.get_program(program_id).is_err() {
                 bail!("❌ Imported program {program_id:?} could not be found on the Aleo Network, please deploy this imported program first before continuing with deployment of {program_id:?}");
             }
             Ok(())
         })?;
 
-        let private_key = self.account().private_key();
+        // Create the deployment transaction, signed by the agent's signer
+        let vm = self.initialize_vm(program)?;
+        let transaction =
+            self.create_deploy_transaction(&vm, program, priority_fee, fee_record, rng)?;
 
-        // Create the deployment transaction
-        let transaction = self.create_deploy_transaction(
-            program,
-            private_key,
-            self.base_url(),
-            priority_fee,
-            fee_record,
-        )?;
+        // Verify the deployment locally, so that invalid deployments are not broadcast
+        self.verify_transaction(&transaction)?;
 
         self.broadcast_transaction(&transaction)
     }
 
-    /// Create a deployment transaction for a program without instantiating the program manager
-    fn create_deploy_transaction(
+    /// Deploy a Leo build directory and its local dependencies to the network
+    ///
+    /// The programs are deployed in import order. Programs already on chain with the same source are
+    /// skipped, and each deployment is confirmed before the next one is broadcast.
+    ///
+    /// # Arguments
+    ///  * `root` - The build directory generated by `leo build`, containing `program.json`,
+    ///    `main.aleo` and the compiled local dependencies in `imports/`
+    ///  * `priority_fee` - The priority fee to pay for each deployment, fees are paid from the public balance
+    ///  * `timeout` - The maximum time to wait for the confirmation of each deployment
+    ///
+    /// # Returns
+    /// * The outcome of each program of the package, in import order
+    ///
+    /// # Example
+    /// ```ignore
+    /// use std::time::Duration;
+    /// use aleo_agent::agent::Agent;
+    /// use aleo_agent::deploy::DeployOutcome;
+    ///
+    /// let agent = Agent::default();
+    /// let outcomes = agent
+    ///     .deploy_package("/contract/build", 1, Duration::from_secs(300))
+    ///     .expect("Failed to deploy package");
+    /// for outcome in outcomes {
+    ///     match outcome {
+    ///         DeployOutcome::Deployed { program_id, transaction_id } => {
+    ///             println!("Deployed {program_id} with tx id: {transaction_id}")
+    ///         }
+    ///         DeployOutcome::Skipped { program_id } => println!("{program_id} is already deployed"),
+    ///     }
+    /// }
+    /// ```
+    pub fn deploy_package<P: Into<PathBuf>>(
         &self,
-        program: &Program,
-        private_key: &PrivateKey,
-        node_url: &String,
+        root: P,
         priority_fee: u64,
-        fee_record: Option<PlaintextRecord>,
-    ) -> anyhow::Result<Transaction> {
-        // Initialize an RNG.
-        let rng = &mut rand::thread_rng();
-        let query = Query::from(node_url);
-
-        // Initialize the VM
-        let vm = Self::initialize_vm(program)?;
-
-        // Create the deployment transaction
-        vm.deploy(
-            private_key,
-            program,
-            fee_record,
-            priority_fee,
-            Some(query),
-            rng,
-        )
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<DeployOutcome>> {
+        let programs = load_package(root)?;
+        let mut outcomes = vec![];
+        for program_id in import_order(&programs)? {
+            let program = &programs[&program_id];
+            if let Ok(onchain) = self.get_program(&program_id) {
+                ensure!(
+                    onchain.to_string() == program.to_string(),
+                    "❌ Program {program_id} is already deployed on chain with a different source"
+                );
+                outcomes.push(DeployOutcome::Skipped { program_id });
+                continue;
+            }
+
+            let transaction_id = self.deploy_program(program, priority_fee, None)?;
+            let confirmed = self.wait_for_confirmation(&transaction_id, timeout)?;
+            ensure!(
+                matches!(confirmed, ConfirmedTransaction::AcceptedDeploy(..)),
+                "❌ Deployment of {program_id} was rejected by the network"
+            );
+            outcomes.push(DeployOutcome::Deployed {
+                program_id,
+                transaction_id,
+            });
+        }
+        Ok(outcomes)
     }
 
-    fn initialize_vm(program: &Program) -> anyhow::Result<VM> {
+    // Initializes an ephemeral VM with the imports of a program, fetched from the agent's node. The
+    // program itself is not added, as it is added during the deployment process
+    pub(crate) fn initialize_vm(&self, program: &Program) -> anyhow::Result<VM> {
         // Create an ephemeral SnarkVM to store the programs
-        // Initialize an RNG and query object for the transaction
         let store = ConsensusStore::open(None)?;
         let vm = VM::from(store)?;
 
         // Resolve imports
         let credits_id = ProgramID::from_str("credits.aleo")?;
-        ProgramManager::get_import_programs_from_chain(program)?
+        self.get_import_programs(program)?
             .iter()
             .try_for_each(|(_, import)| {
                 if import.id() != &credits_id {
@@ -101,3 +168,109 @@ impl Agent {
         Ok(vm)
     }
 }
+
+/// The outcome of the deployment of a program of a package, see [Agent::deploy_package].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeployOutcome {
+    /// The program was deployed and confirmed.
+    Deployed {
+        program_id: ProgramID,
+        transaction_id: String,
+    },
+    /// The program was already deployed with the same source.
+    Skipped { program_id: ProgramID },
+}
+
+impl DeployOutcome {
+    pub fn program_id(&self) -> &ProgramID {
+        match self {
+            DeployOutcome::Deployed { program_id, .. } | DeployOutcome::Skipped { program_id } => {
+                program_id
+            }
+        }
+    }
+}
+
+// Load the main program of a build directory and the local dependencies found in `imports/`
+fn load_package<P: Into<PathBuf>>(root: P) -> anyhow::Result<IndexMap<ProgramID, Program>> {
+    let root = root.into();
+    let main = ProgramManager::load_program_from_path(&root)?;
+
+    let mut programs = IndexMap::new();
+    let imports_dir = root.join("imports");
+    if imports_dir.is_dir() {
+        for entry in std::fs::read_dir(&imports_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("aleo") {
+                continue;
+            }
+            let program = Program::from_str(&std::fs::read_to_string(&path)?)
+                .map_err(|error| anyhow::anyhow!("Failed to parse {}: {error}", path.display()))?;
+            programs.insert(*program.id(), program);
+        }
+    }
+    programs.insert(*main.id(), main);
+    Ok(programs)
+}
+
+// Order the programs so that every program comes after the local programs it imports
+pub(crate) fn import_order(
+    programs: &IndexMap<ProgramID, Program>,
+) -> anyhow::Result<Vec<ProgramID>> {
+    fn visit(
+        program_id: &ProgramID,
+        programs: &IndexMap<ProgramID, Program>,
+        visiting: &mut HashSet<ProgramID>,
+        order: &mut Vec<ProgramID>,
+    ) -> anyhow::Result<()> {
+        if order.contains(program_id) {
+            return Ok(());
+        }
+        // Imports that are not part of the package must already be deployed
+        let Some(program) = programs.get(program_id) else {
+            return Ok(());
+        };
+        ensure!(
+            visiting.insert(*program_id),
+            "Circular dependency discovered in program imports at {program_id}"
+        );
+        for import_id in program.imports().keys() {
+            visit(import_id, programs, visiting, order)?;
+        }
+        visiting.remove(program_id);
+        order.push(*program_id);
+        Ok(())
+    }
+
+    let mut order = vec![];
+    let mut visiting = HashSet::new();
+    for program_id in programs.keys() {
+        visit(program_id, programs, &mut visiting, &mut order)?;
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn program(source: &str) -> (ProgramID, Program) {
+        let program = Program::from_str(source).unwrap();
+        (*program.id(), program)
+    }
+
+    #[test]
+    fn test_import_order() {
+        let programs = IndexMap::from_iter([
+            program("import b_dep.aleo;\nimport c_dep.aleo;\nprogram a_main.aleo;\nfunction main:\n    input r0 as u8.public;\n"),
+            program("import c_dep.aleo;\nprogram b_dep.aleo;\nfunction main:\n    input r0 as u8.public;\n"),
+            program("import credits.aleo;\nprogram c_dep.aleo;\nfunction main:\n    input r0 as u8.public;\n"),
+        ]);
+        let order = import_order(&programs)
+            .unwrap()
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec!["c_dep.aleo", "b_dep.aleo", "a_main.aleo"]);
+    }
+}
@@ -0,0 +1,294 @@
+//! An in-process devnet for integration tests
+//!
+//! The [Devnet] runs a ledger from its own genesis block, funds test accounts with public credits,
+//! and serves the subset of the node REST API used by the agent on a local port. Transactions
+//! broadcast to it wait in a mempool until a block is produced with [Devnet::advance], or right
+//! away with [Devnet::set_auto_advance].
+//!
+//! Proving still needs the snarkVM parameters, which are downloaded on first use and then cached.
+//!
+//! The devnet is only available with the `test-utils` feature.
+//!
+//! # Example
+//! ```ignore
+//! use aleo_agent::agent::{TransferArgs, TransferType};
+//! use aleo_agent::devnet::Devnet;
+//! use aleo_agent::MICROCREDITS;
+//!
+//! let devnet = Devnet::start(2, 100 * MICROCREDITS).unwrap();
+//! let alice = devnet.agent(0);
+//! let bob = devnet.account(1).address();
+//!
+//! let args = TransferArgs::from(MICROCREDITS, *bob, 0, None, TransferType::Public);
+//! alice.transfer(args).unwrap();
+//! devnet.advance().unwrap();
+//! ```
+
+use std::io::Read;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use anyhow::{anyhow, Result};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::account::Account;
+use crate::agent::Agent;
+use crate::backend::{is_not_found, Backend, MemoryLedger};
+
+use super::*;
+
+// The seed of the devnet keys, so that test accounts are the same on every run
+const DEVNET_SEED: u64 = 0;
+
+/// A local ledger served over HTTP.
+pub struct Devnet {
+    ledger: Arc<MemoryLedger>,
+    server: Arc<Server>,
+    handle: Option<JoinHandle<()>>,
+    accounts: Vec<Account>,
+    url: String,
+}
+
+impl Devnet {
+    /// Starts a devnet on a free local port.
+    ///
+    /// # Arguments
+    /// * `accounts` - The number of test accounts
+    /// * `microcredits` - The public balance of each test account
+    pub fn start(accounts: usize, microcredits: u64) -> Result<Self> {
+        let ledger = Arc::new(MemoryLedger::new(DEVNET_SEED)?);
+        ledger.set_auto_advance(false);
+
+        let mut rng = ChaChaRng::seed_from_u64(DEVNET_SEED + 1);
+        let accounts = (0..accounts)
+            .map(|_| {
+                let private_key = PrivateKey::new(&mut rng)?;
+                Ok(Account::from_keys(
+                    private_key,
+                    ViewKey::try_from(&private_key)?,
+                    Address::try_from(&private_key)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !accounts.is_empty() {
+            for account in &accounts {
+                ledger.fund(account.address(), microcredits)?;
+            }
+            ledger.advance()?;
+        }
+
+        let server = Arc::new(
+            Server::http("127.0.0.1:0")
+                .map_err(|error| anyhow!("Failed to start the devnet server: {error}"))?,
+        );
+        let port = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| anyhow!("The devnet server is not bound to a port"))?
+            .port();
+        let handle = thread::spawn({
+            let server = server.clone();
+            let ledger = ledger.clone();
+            move || {
+                for request in server.incoming_requests() {
+                    handle(&ledger, request);
+                }
+            }
+        });
+
+        Ok(Self {
+            ledger,
+            server,
+            handle: Some(handle),
+            accounts,
+            url: format!("http://127.0.0.1:{port}"),
+        })
+    }
+
+    /// Returns the base URL of the devnet, to be used as the agent's URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the ledger of the devnet.
+    pub fn ledger(&self) -> &Arc<MemoryLedger> {
+        &self.ledger
+    }
+
+    /// Returns a funded test account.
+    ///
+    /// # Panics
+    /// If there is no test account at `index`.
+    pub fn account(&self, index: usize) -> &Account {
+        &self.accounts[index]
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+
+    /// Returns an agent connected to the devnet, signing with a test account.
+    ///
+    /// # Panics
+    /// If there is no test account at `index`.
+    pub fn agent(&self, index: usize) -> Agent {
+        Agent::builder()
+            .with_url(self.url.clone())
+            .with_network(DEFAULT_TESTNET)
+            .with_account(self.accounts[index].clone())
+            .build()
+    }
+
+    /// Produces a block with the transactions of the mempool.
+    pub fn advance(&self) -> Result<Block> {
+        self.ledger.advance()
+    }
+
+    /// Produces `count` blocks.
+    pub fn advance_by(&self, count: u32) -> Result<()> {
+        for _ in 0..count {
+            self.ledger.advance()?;
+        }
+        Ok(())
+    }
+
+    /// Produces a block for each broadcast transaction, so that calls waiting for a confirmation,
+    /// such as [Agent::deploy_package], do not need a block to be produced from the test.
+    pub fn set_auto_advance(&self, auto_advance: bool) {
+        self.ledger.set_auto_advance(auto_advance);
+    }
+
+    /// Returns the height of the latest block.
+    pub fn height(&self) -> u32 {
+        self.ledger.height()
+    }
+
+    /// Returns the transactions waiting for the next block.
+    pub fn mempool(&self) -> Vec<Transaction> {
+        self.ledger.mempool()
+    }
+}
+
+impl Drop for Devnet {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// Answers a request, errors being returned with their message as a 404 response for missing
+// resources, as a node does, or else as a 400 response
+fn handle(ledger: &MemoryLedger, mut request: Request) {
+    let mut body = String::new();
+    let response = match request.as_reader().read_to_string(&mut body) {
+        Ok(_) => route(ledger, request.method(), request.url(), &body),
+        Err(error) => Err(error.into()),
+    };
+    let response = match response {
+        Ok(json) => Response::from_string(json).with_header(
+            Header::from_bytes("Content-Type", "application/json")
+                .expect("Invalid content type header"),
+        ),
+        Err(error) => {
+            let status = if is_not_found(&error) { 404 } else { 400 };
+            Response::from_string(error.to_string()).with_status_code(status)
+        }
+    };
+    let _ = request.respond(response);
+}
+
+// Serves the node REST API, the network segment of the path being ignored
+fn route(ledger: &MemoryLedger, method: &Method, url: &str, body: &str) -> Result<String> {
+    let path = url
+        .trim_start_matches('/')
+        .split_once('/')
+        .map(|(_, path)| format!("/{path}"))
+        .unwrap_or_default();
+    match (method, path.as_str()) {
+        (Method::Post, "/transaction/broadcast") => ledger.broadcast(&serde_json::from_str(body)?),
+        (Method::Get, path) => ledger.get(path),
+        _ => Err(anyhow!("Unsupported request {method} {url}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::agent::{TransferArgs, TransferType};
+
+    const LIB: &str = r"
+program lib_devnet.aleo;
+
+function double:
+    input r0 as u32.public;
+    add r0 r0 into r1;
+    output r1 as u32.public;
+";
+
+    const APP: &str = r"
+import lib_devnet.aleo;
+
+program app_devnet.aleo;
+
+function quadruple:
+    input r0 as u32.public;
+    call lib_devnet.aleo/double r0 into r1;
+    call lib_devnet.aleo/double r1 into r2;
+    output r2 as u32.public;
+";
+
+    #[test]
+    fn test_devnet_transfer() {
+        let devnet = Devnet::start(2, 100 * MICROCREDITS).unwrap();
+        let alice = devnet.agent(0);
+        let bob = devnet.agent(1);
+        assert_eq!(alice.get_public_balance().unwrap(), 100 * MICROCREDITS);
+
+        let args = TransferArgs::from(
+            MICROCREDITS,
+            *bob.account().address(),
+            0,
+            None,
+            TransferType::Public,
+        );
+        alice.transfer(args).unwrap();
+        assert_eq!(devnet.mempool().len(), 1);
+        devnet.advance().unwrap();
+
+        assert_eq!(bob.get_public_balance().unwrap(), 101 * MICROCREDITS);
+        assert!(alice.get_public_balance().unwrap() < 99 * MICROCREDITS);
+
+        // Unknown serial numbers are answered with a 404, telling unspent records apart
+        assert!(!alice.is_spent(Field::from_u64(1)).unwrap());
+    }
+
+    #[test]
+    fn test_devnet_imports() {
+        let devnet = Devnet::start(1, 1_000 * MICROCREDITS).unwrap();
+        devnet.set_auto_advance(true);
+        let alice = devnet.agent(0);
+
+        // The import is resolved from the devnet, where it is the only deployment
+        let lib = Program::from_str(LIB).unwrap();
+        let app = Program::from_str(APP).unwrap();
+        alice.deploy_program(&lib, 0, None).unwrap();
+        alice.deploy_program(&app, 0, None).unwrap();
+        assert_eq!(alice.get_program(app.id()).unwrap(), app);
+
+        let receipt = alice
+            .program("app_devnet.aleo")
+            .unwrap()
+            .execute_program("quadruple", ["3u32"].into_iter(), 0, None)
+            .unwrap();
+        assert_eq!(
+            receipt.outputs().unwrap(),
+            vec![Value::from_str("12u32").unwrap()]
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Structured comparison of two versions of a program
+
+use std::fmt;
+
+use indexmap::IndexMap;
+
+use super::*;
+
+/// The names of the items added, removed and changed between two versions of a program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ItemDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ItemDiff {
+    // Compares two sets of items by their name and source
+    fn new<K: ToString, V: ToString>(old: &IndexMap<K, V>, new: &IndexMap<K, V>) -> Self
+    where
+        K: std::hash::Hash + Eq,
+    {
+        let mut diff = ItemDiff::default();
+        for (name, item) in new {
+            match old.get(name) {
+                None => diff.added.push(name.to_string()),
+                Some(old_item) if old_item.to_string() != item.to_string() => {
+                    diff.changed.push(name.to_string())
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|name| !new.contains_key(*name))
+            .map(ToString::to_string)
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The differences between two versions of a program.
+///
+/// # Example
+/// ```ignore
+/// use aleo_agent::agent::Agent;
+/// use aleo_agent::program::ProgramManager;
+///
+/// let agent = Agent::default();
+/// let local_program = ProgramManager::load_program_from_path("/contract/build").unwrap();
+/// let pm = agent.program(&local_program.id().to_string()).unwrap();
+/// let diff = pm.diff(&local_program).expect("Failed to compare with the deployed program");
+/// println!("{diff}");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramDiff {
+    pub program_id: ProgramID,
+    pub imports: ItemDiff,
+    pub structs: ItemDiff,
+    pub records: ItemDiff,
+    pub mappings: ItemDiff,
+    pub closures: ItemDiff,
+    pub functions: ItemDiff,
+}
+
+impl ProgramDiff {
+    /// Compares the `old` and `new` versions of a program.
+    pub fn new(old: &Program, new: &Program) -> Self {
+        Self {
+            program_id: *new.id(),
+            imports: ItemDiff::new(old.imports(), new.imports()),
+            structs: ItemDiff::new(old.structs(), new.structs()),
+            records: ItemDiff::new(old.records(), new.records()),
+            mappings: ItemDiff::new(old.mappings(), new.mappings()),
+            closures: ItemDiff::new(old.closures(), new.closures()),
+            functions: ItemDiff::new(old.functions(), new.functions()),
+        }
+    }
+
+    /// Returns `true` if both versions are identical.
+    pub fn is_empty(&self) -> bool {
+        self.sections().iter().all(|(_, diff)| diff.is_empty())
+    }
+
+    fn sections(&self) -> [(&'static str, &ItemDiff); 6] {
+        [
+            ("imports", &self.imports),
+            ("structs", &self.structs),
+            ("records", &self.records),
+            ("mappings", &self.mappings),
+            ("closures", &self.closures),
+            ("functions", &self.functions),
+        ]
+    }
+}
+
+impl fmt::Display for ProgramDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "{}: no changes", self.program_id);
+        }
+        write!(f, "{}:", self.program_id)?;
+        for (section, diff) in self.sections() {
+            for name in &diff.added {
+                write!(f, "\n  + {section} {name}")?;
+            }
+            for name in &diff.removed {
+                write!(f, "\n  - {section} {name}")?;
+            }
+            for name in &diff.changed {
+                write!(f, "\n  ~ {section} {name}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_program_diff() {
+        let old = Program::from_str(
+            r"
+program diff_test.aleo;
+
+mapping counts:
+    key as address.public;
+    value as u64.public;
+
+function add:
+    input r0 as u32.public;
+    input r1 as u32.public;
+    add r0 r1 into r2;
+    output r2 as u32.public;
+
+function sub:
+    input r0 as u32.public;
+    input r1 as u32.public;
+    sub r0 r1 into r2;
+    output r2 as u32.public;
+",
+        )
+        .unwrap();
+        let new = Program::from_str(
+            r"
+program diff_test.aleo;
+
+mapping counts:
+    key as address.public;
+    value as u64.public;
+
+function add:
+    input r0 as u32.public;
+    input r1 as u32.public;
+    add.w r0 r1 into r2;
+    output r2 as u32.public;
+
+function mul:
+    input r0 as u32.public;
+    input r1 as u32.public;
+    mul r0 r1 into r2;
+    output r2 as u32.public;
+",
+        )
+        .unwrap();
+
+        assert!(ProgramDiff::new(&old, &old).is_empty());
+
+        let diff = ProgramDiff::new(&old, &new);
+        assert!(diff.mappings.is_empty());
+        assert_eq!(diff.functions.added, vec!["mul"]);
+        assert_eq!(diff.functions.removed, vec!["sub"]);
+        assert_eq!(diff.functions.changed, vec!["add"]);
+        assert_eq!(
+            diff.to_string(),
+            "diff_test.aleo:\n  + functions mul\n  - functions sub\n  ~ functions add"
+        );
+    }
+}
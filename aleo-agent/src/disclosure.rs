@@ -0,0 +1,290 @@
+//! Selective disclosure of records and transitions
+//!
+//! A record is encrypted with a record view key, and the private inputs and outputs of a transition
+//! with keys derived from its transition view key. Both keys are derived from the account view key,
+//! but reveal nothing else: sharing them lets an auditor decrypt a single record or transition,
+//! and check that the key matches it, without receiving the account view key.
+//!
+//! # Example
+//! ```ignore
+//! use aleo_agent::agent::Agent;
+//! use aleo_agent::disclosure::TransitionDisclosure;
+//!
+//! // The owner discloses a transition
+//! let agent = Agent::default();
+//! let transaction = agent.get_transaction("at1...").unwrap();
+//! let transition = transaction.transitions().next().unwrap();
+//! let disclosure = TransitionDisclosure::new(transition, agent.view_key().unwrap()).unwrap();
+//! let json = serde_json::to_string(&disclosure).unwrap();
+//!
+//! // The auditor decrypts it
+//! let disclosure: TransitionDisclosure = serde_json::from_str(&json).unwrap();
+//! let values = disclosure.decrypt().expect("Invalid disclosure");
+//! println!("{:?}", values.outputs);
+//! ```
+
+use anyhow::{anyhow, bail, ensure, Result};
+use serde::{Deserialize, Serialize};
+use snarkvm::console::program::ValueType;
+use snarkvm::ledger::block::{Input, Output};
+
+use crate::receipt::{input_view_key, output_view_key};
+
+use super::*;
+
+/// Derives the record view key of a record owned by the view key.
+pub fn record_view_key(record: &CiphertextRecord, view_key: &ViewKey) -> Result<Field> {
+    ensure!(
+        record.is_owner(view_key),
+        "The record is not owned by the view key"
+    );
+    Ok((*record.nonce() * **view_key).to_x_coordinate())
+}
+
+/// Derives the transition view key of a transition signed by the view key.
+pub fn transition_view_key(transition: &Transition, view_key: &ViewKey) -> Result<Field> {
+    let tvk = (*transition.tpk() * **view_key).to_x_coordinate();
+    ensure!(
+        is_transition_view_key(transition, &tvk)?,
+        "Transition {} was not signed by the view key",
+        transition.id()
+    );
+    Ok(tvk)
+}
+
+// Checks a transition view key against the transition commitment
+fn is_transition_view_key(transition: &Transition, tvk: &Field) -> Result<bool> {
+    Ok(&CurrentNetwork::hash_psd2(&[*tvk])? == transition.tcm())
+}
+
+/// A record disclosed with its record view key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordDisclosure {
+    pub program_id: ProgramID,
+    pub record_name: Identifier,
+    pub commitment: Field,
+    pub record: CiphertextRecord,
+    pub record_view_key: Field,
+}
+
+impl RecordDisclosure {
+    /// Discloses a record output of a transition.
+    ///
+    /// # Arguments
+    /// * `transition` - The transition creating the record
+    /// * `index` - The index of the record among the outputs of the transition
+    /// * `program` - The program of the transition, declaring the record type
+    /// * `view_key` - The view key of the record owner
+    pub fn from_transition(
+        transition: &Transition,
+        index: usize,
+        program: &Program,
+        view_key: &ViewKey,
+    ) -> Result<Self> {
+        let Some(Output::Record(commitment, _, Some(record))) = transition.outputs().get(index)
+        else {
+            bail!(
+                "Output #{index} of transition {} is not a record",
+                transition.id()
+            );
+        };
+        let function = program.get_function(transition.function_name())?;
+        let record_name = match function
+            .outputs()
+            .get_index(index)
+            .map(|output| output.value_type())
+        {
+            Some(ValueType::Record(record_name)) => *record_name,
+            _ => bail!(
+                "Output #{index} of function {} is not a record",
+                transition.function_name()
+            ),
+        };
+        Ok(Self {
+            program_id: *transition.program_id(),
+            record_name,
+            commitment: *commitment,
+            record: record.clone(),
+            record_view_key: record_view_key(record, view_key)?,
+        })
+    }
+
+    /// Decrypts the record, checking that it matches its commitment.
+    pub fn decrypt(&self) -> Result<PlaintextRecord> {
+        let record = self
+            .record
+            .decrypt_symmetric_unchecked(&self.record_view_key)
+            .map_err(|_| anyhow!("The record view key does not match the record"))?;
+        ensure!(
+            record.to_commitment(&self.program_id, &self.record_name)? == self.commitment,
+            "The decrypted record does not match its commitment"
+        );
+        Ok(record)
+    }
+}
+
+/// A transition disclosed with its transition view key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionDisclosure {
+    pub transition: Transition,
+    pub transition_view_key: Field,
+}
+
+/// The inputs and outputs of a disclosed transition.
+///
+/// Constant, public and private values are known; records and futures are `None`, records being
+/// disclosed separately with [RecordDisclosure].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisclosedValues {
+    pub inputs: Vec<Option<Plaintext>>,
+    pub outputs: Vec<Option<Plaintext>>,
+}
+
+impl TransitionDisclosure {
+    /// Discloses a transition signed by the view key.
+    pub fn new(transition: &Transition, view_key: &ViewKey) -> Result<Self> {
+        Ok(Self {
+            transition: transition.clone(),
+            transition_view_key: transition_view_key(transition, view_key)?,
+        })
+    }
+
+    /// Decrypts the private inputs and outputs of the transition, checking that the transition view
+    /// key matches the transition.
+    pub fn decrypt(&self) -> Result<DisclosedValues> {
+        let transition = &self.transition;
+        let tvk = &self.transition_view_key;
+        ensure!(
+            is_transition_view_key(transition, tvk)?,
+            "The transition view key does not match transition {}",
+            transition.id()
+        );
+
+        let inputs = transition
+            .inputs()
+            .iter()
+            .enumerate()
+            .map(|(index, input)| match input {
+                Input::Constant(_, Some(plaintext)) | Input::Public(_, Some(plaintext)) => {
+                    Ok(Some(plaintext.clone()))
+                }
+                Input::Private(_, Some(ciphertext)) => Ok(Some(
+                    ciphertext.decrypt_symmetric(input_view_key(transition, tvk, index)?)?,
+                )),
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let outputs = transition
+            .outputs()
+            .iter()
+            .enumerate()
+            .map(|(index, output)| match output {
+                Output::Constant(_, Some(plaintext)) | Output::Public(_, Some(plaintext)) => {
+                    Ok(Some(plaintext.clone()))
+                }
+                Output::Private(_, Some(ciphertext)) => Ok(Some(
+                    ciphertext.decrypt_symmetric(output_view_key(transition, tvk, index)?)?,
+                )),
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(DisclosedValues { inputs, outputs })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    use super::*;
+    use crate::account::Account;
+
+    const PROGRAM: &str = r"
+program disclosure_test.aleo;
+
+function main:
+    input r0 as u32.public;
+    input r1 as u32.private;
+    mul r0 r1 into r2;
+    output r2 as u32.private;
+";
+
+    #[test]
+    fn test_record_disclosure() {
+        let rng = &mut rand::thread_rng();
+        let account = Account::new().unwrap();
+        let randomizer = Scalar::rand(rng);
+        let nonce = CurrentNetwork::g_scalar_multiply(&randomizer);
+        let plaintext = PlaintextRecord::from_str(&format!(
+            "{{ owner: {}.private, microcredits: 5u64.private, _nonce: {nonce}.public }}",
+            account.address()
+        ))
+        .unwrap();
+        let record = plaintext.encrypt(randomizer).unwrap();
+
+        let program_id = ProgramID::from_str("credits.aleo").unwrap();
+        let record_name = Identifier::from_str("credits").unwrap();
+        let mut disclosure = RecordDisclosure {
+            program_id,
+            record_name,
+            commitment: plaintext.to_commitment(&program_id, &record_name).unwrap(),
+            record: record.clone(),
+            record_view_key: record_view_key(&record, account.view_key()).unwrap(),
+        };
+        assert_eq!(disclosure.decrypt().unwrap(), plaintext);
+
+        let other = Account::new().unwrap();
+        assert!(record_view_key(&record, other.view_key()).is_err());
+
+        disclosure.commitment = Field::from_u64(1);
+        assert!(disclosure.decrypt().is_err());
+    }
+
+    #[test]
+    fn test_transition_disclosure() {
+        let rng = &mut ChaChaRng::seed_from_u64(0);
+        let account = Account::from_seed(1).unwrap();
+        let program = Program::from_str(PROGRAM).unwrap();
+        let mut process = Process::load().unwrap();
+        process.add_program(&program).unwrap();
+        let inputs = [
+            Value::from_str("2u32").unwrap(),
+            Value::from_str("3u32").unwrap(),
+        ];
+        let authorization = process
+            .authorize::<CurrentAleo, _>(
+                account.expose_private_key().unwrap(),
+                program.id(),
+                "main",
+                inputs.iter(),
+                rng,
+            )
+            .unwrap();
+        let (_, trace) = process
+            .execute::<CurrentAleo, _>(authorization, rng)
+            .unwrap();
+        let transitions = trace.transitions();
+        let transition = &transitions[0];
+
+        // The owner discloses the transition, the auditor decrypts it without the view key
+        let disclosure = TransitionDisclosure::new(transition, account.view_key()).unwrap();
+        let json = serde_json::to_string(&disclosure).unwrap();
+        let disclosure: TransitionDisclosure = serde_json::from_str(&json).unwrap();
+        let values = disclosure.decrypt().unwrap();
+        let plaintext = |value: &str| Some(Plaintext::from_str(value).unwrap());
+        assert_eq!(values.inputs, vec![plaintext("2u32"), plaintext("3u32")]);
+        assert_eq!(values.outputs, vec![plaintext("6u32")]);
+
+        let other = Account::from_seed(2).unwrap();
+        assert!(TransitionDisclosure::new(transition, other.view_key()).is_err());
+
+        let tampered = TransitionDisclosure {
+            transition_view_key: Field::from_u64(1),
+            ..disclosure
+        };
+        assert!(tampered.decrypt().is_err());
+    }
+}
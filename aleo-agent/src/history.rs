@@ -0,0 +1,501 @@
+//! Local transaction history of the agent's account
+//!
+//! The [HistoryIndexer] walks blocks and records every transition touching the account, using only
+//! the view key: transitions signed by the account, public transfers to or from its address, and
+//! records it owns. Credits transfers and fees are decoded with their amounts.
+//!
+//! The index is held in memory. Save it with [HistoryIndexer::save] and resume it with
+//! [Agent::load_history], so that only the blocks produced since are scanned.
+//!
+//! # Example
+//! ```ignore
+//! use aleo_agent::agent::Agent;
+//!
+//! let agent = Agent::default();
+//! let mut history = agent.load_history("history.json").unwrap_or_else(|_| agent.history(0));
+//! history.sync().expect("Failed to index blocks");
+//! history.save("history.json").expect("Failed to save the history");
+//! for entry in history.page(0, 20) {
+//!     println!("{} {:?} {} {:?}", entry.block_height, entry.direction, entry.amount, entry.kind);
+//! }
+//! ```
+
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{bail, ensure, Result};
+use serde::{Deserialize, Serialize};
+use snarkvm::console::program::Argument;
+use snarkvm::ledger::block::{Input, Output};
+
+use crate::agent::{Agent, Credits};
+use crate::disclosure::transition_view_key;
+use crate::keystore::write_private_file;
+use crate::receipt::input_view_key;
+
+use super::*;
+
+// The number of blocks fetched per request
+const BATCH_SIZE: u32 = 50;
+
+/// The kind of a history entry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryKind {
+    /// A transfer of credits, public or private.
+    Transfer,
+    /// A fee paid for a transaction.
+    Fee,
+    /// A call to a function other than a credits transfer, signed by the account.
+    Call,
+    /// A record received by the account from a call to a function other than a credits transfer.
+    Record,
+}
+
+/// The direction of a history entry, from the point of view of the account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+    /// A transfer from the account to itself.
+    ToSelf,
+}
+
+/// A transition touching the account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub block_height: u32,
+    /// The timestamp of the block, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    pub transaction_id: TransactionID,
+    pub transition_id: TransitionID,
+    pub program_id: ProgramID,
+    pub function_name: Identifier,
+    pub kind: HistoryKind,
+    pub direction: Direction,
+    /// The amount of microcredits transferred or paid, 0 if unknown or not applicable.
+    pub amount: u64,
+    /// The other party of a transfer, if it is public or was signed by the account.
+    pub counterparty: Option<Address>,
+}
+
+/// An in-memory index of the transitions touching the account of an agent.
+pub struct HistoryIndexer<'agent> {
+    agent: &'agent Agent,
+    entries: Vec<HistoryEntry>,
+    next_height: u32,
+}
+
+impl Agent {
+    /// Creates a history indexer for the agent's account, starting at `start_height`.
+    pub fn history(&self, start_height: u32) -> HistoryIndexer {
+        HistoryIndexer {
+            agent: self,
+            entries: vec![],
+            next_height: start_height,
+        }
+    }
+
+    /// Resumes a history indexer saved with [HistoryIndexer::save].
+    ///
+    /// # Errors
+    /// If the file cannot be read, or holds the history of another address.
+    pub fn load_history<P: AsRef<Path>>(&self, path: P) -> Result<HistoryIndexer> {
+        let file: HistoryFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let address = self.address();
+        ensure!(
+            file.address == address,
+            "The history is the one of {}, not of {address}",
+            file.address
+        );
+        Ok(HistoryIndexer {
+            agent: self,
+            entries: file.entries,
+            next_height: file.next_height,
+        })
+    }
+}
+
+// The saved state of a history indexer
+#[derive(Serialize, Deserialize)]
+struct HistoryFile {
+    address: Address,
+    next_height: u32,
+    entries: Vec<HistoryEntry>,
+}
+
+impl<'agent> HistoryIndexer<'agent> {
+    /// Indexes the blocks up to the latest block.
+    ///
+    /// # Returns
+    /// The number of new entries.
+    pub fn sync(&mut self) -> Result<usize> {
+        let latest_height = self.agent.get_latest_block_height()?;
+        self.sync_to(latest_height + 1)
+    }
+
+    /// Indexes the blocks up to `end_height` (exclusive).
+    ///
+    /// The blocks are indexed by batch, and a batch is only added to the index once all its blocks
+    /// are indexed: after a failure, the index resumes at the first block of the failed batch.
+    ///
+    /// # Returns
+    /// The number of new entries.
+    ///
+    /// # Errors
+    /// If the node skips a block or returns no block at the next height.
+    pub fn sync_to(&mut self, end_height: u32) -> Result<usize> {
+        let count = self.entries.len();
+        while self.next_height < end_height {
+            let batch_end = end_height.min(self.next_height + BATCH_SIZE);
+            let mut next_height = self.next_height;
+            let mut entries = vec![];
+            for block in self
+                .agent
+                .get_blocks_in_range(self.next_height, batch_end)?
+            {
+                ensure!(
+                    block.height() == next_height,
+                    "Expected block {next_height}, found block {}",
+                    block.height()
+                );
+                entries.extend(self.index_block(&block)?);
+                next_height += 1;
+            }
+            if next_height == self.next_height {
+                bail!("The node returned no block at height {next_height}");
+            }
+            self.entries.extend(entries);
+            self.next_height = next_height;
+        }
+        Ok(self.entries.len() - count)
+    }
+
+    /// Returns the height of the next block to index.
+    pub fn next_height(&self) -> u32 {
+        self.next_height
+    }
+
+    /// Returns the entries, oldest first.
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Writes the entries and the next height to index to a JSON file, see [Agent::load_history].
+    ///
+    /// The file is not encrypted: it reveals the activity of the account to anyone reading it. On
+    /// Unix, it is only readable and writable by its owner, mode `0600`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = HistoryFile {
+            address: self.agent.address(),
+            next_height: self.next_height,
+            entries: self.entries.clone(),
+        };
+        write_private_file(path, serde_json::to_string_pretty(&file)?.as_bytes())
+    }
+
+    /// Returns a page of entries, newest first.
+    ///
+    /// # Arguments
+    /// * `page` - The index of the page, starting at 0
+    /// * `page_size` - The number of entries per page
+    pub fn page(&self, page: usize, page_size: usize) -> Vec<HistoryEntry> {
+        self.entries[page_range(self.entries.len(), page, page_size)]
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the number of pages of `page_size` entries.
+    pub fn page_count(&self, page_size: usize) -> usize {
+        self.entries.len().div_ceil(page_size.max(1))
+    }
+
+    // Returns the entries of the transitions of a block touching the account
+    fn index_block(&self, block: &Block) -> Result<Vec<HistoryEntry>> {
+        let view_key = self.agent.view_key()?;
+        let address = self.agent.address();
+        let mut entries = vec![];
+        for confirmed in block.transactions().iter() {
+            let transaction = confirmed.transaction();
+            for transition in transaction.transitions() {
+                let touches = index_transition(transition, view_key, &address)?;
+                entries.extend(touches.into_iter().map(
+                    |(kind, direction, amount, counterparty)| HistoryEntry {
+                        block_height: block.height(),
+                        timestamp: block.timestamp(),
+                        transaction_id: transaction.id(),
+                        transition_id: *transition.id(),
+                        program_id: *transition.program_id(),
+                        function_name: *transition.function_name(),
+                        kind,
+                        direction,
+                        amount,
+                        counterparty,
+                    },
+                ));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+type Touch = (HistoryKind, Direction, u64, Option<Address>);
+
+// Decodes how a transition touches the account
+fn index_transition(
+    transition: &Transition,
+    view_key: &ViewKey,
+    address: &Address,
+) -> Result<Vec<Touch>> {
+    // The transition view key is only known for transitions signed by the account
+    let tvk = transition_view_key(transition, view_key).ok();
+    let received = received_credits(transition, view_key)?;
+
+    if transition.program_id() != &ProgramID::from_str("credits.aleo")? {
+        let mut touches = vec![];
+        if tvk.is_some() {
+            touches.push((HistoryKind::Call, Direction::Outgoing, 0, None));
+        }
+        if let Some(amount) = received {
+            touches.push((HistoryKind::Record, Direction::Incoming, amount, None));
+        }
+        return Ok(touches);
+    }
+
+    let function_name = transition.function_name().to_string();
+    let touch = match function_name.as_str() {
+        "fee_public" | "fee_private" => {
+            // The base and priority fees are public inputs
+            let offset = usize::from(function_name == "fee_private");
+            let fee = input_u64(transition, offset, None).unwrap_or_default()
+                + input_u64(transition, offset + 1, None).unwrap_or_default();
+            tvk.map(|_| (HistoryKind::Fee, Direction::Outgoing, fee, None))
+        }
+        "transfer_public" | "transfer_public_as_signer" => {
+            // The finalize arguments are the sender, the recipient and the amount
+            let sender = future_address(transition, 0);
+            let recipient = future_address(transition, 1);
+            let amount = future_u64(transition, 2).unwrap_or_default();
+            transfer(address, sender, recipient, amount)
+        }
+        "transfer_private_to_public" => {
+            let recipient = input_address(transition, 1, tvk.as_ref());
+            let amount = input_u64(transition, 2, tvk.as_ref()).unwrap_or_default();
+            let sender = tvk.map(|_| *address);
+            transfer(address, sender, recipient, amount)
+        }
+        "transfer_private" | "transfer_public_to_private" => {
+            let (recipient_index, amount_index) = match function_name.as_str() {
+                "transfer_private" => (1, 2),
+                _ => (0, 1),
+            };
+            match tvk {
+                Some(tvk) => {
+                    let recipient = input_address(transition, recipient_index, Some(&tvk));
+                    let amount =
+                        input_u64(transition, amount_index, Some(&tvk)).unwrap_or_default();
+                    transfer(address, Some(*address), recipient, amount)
+                }
+                None => received
+                    .map(|amount| (HistoryKind::Transfer, Direction::Incoming, amount, None)),
+            }
+        }
+        _ => match (tvk, received) {
+            (Some(_), _) => Some((HistoryKind::Call, Direction::Outgoing, 0, None)),
+            (None, Some(amount)) => Some((HistoryKind::Record, Direction::Incoming, amount, None)),
+            (None, None) => None,
+        },
+    };
+    Ok(touch.into_iter().collect())
+}
+
+// Classifies a transfer between a sender and a recipient
+fn transfer(
+    address: &Address,
+    sender: Option<Address>,
+    recipient: Option<Address>,
+    amount: u64,
+) -> Option<Touch> {
+    let outgoing = sender.as_ref() == Some(address);
+    let incoming = recipient.as_ref() == Some(address);
+    let (direction, counterparty) = match (outgoing, incoming) {
+        (true, true) => (Direction::ToSelf, recipient),
+        (true, false) => (Direction::Outgoing, recipient),
+        (false, true) => (Direction::Incoming, sender),
+        (false, false) => return None,
+    };
+    Some((HistoryKind::Transfer, direction, amount, counterparty))
+}
+
+// Sums the microcredits of the credits records output to the account, if any
+fn received_credits(transition: &Transition, view_key: &ViewKey) -> Result<Option<u64>> {
+    let mut received = None;
+    for output in transition.outputs() {
+        if let Output::Record(_, _, Some(record)) = output {
+            if record.is_owner(view_key) {
+                let amount = record.decrypt(view_key)?.microcredits().unwrap_or_default();
+                received = Some(received.unwrap_or_default() + amount);
+            }
+        }
+    }
+    Ok(received)
+}
+
+// Reads a public input, or a private input with the transition view key
+fn input_plaintext(
+    transition: &Transition,
+    index: usize,
+    tvk: Option<&Field>,
+) -> Option<Plaintext> {
+    match (transition.inputs().get(index)?, tvk) {
+        (Input::Constant(_, Some(plaintext)) | Input::Public(_, Some(plaintext)), _) => {
+            Some(plaintext.clone())
+        }
+        (Input::Private(_, Some(ciphertext)), Some(tvk)) => ciphertext
+            .decrypt_symmetric(input_view_key(transition, tvk, index).ok()?)
+            .ok(),
+        _ => None,
+    }
+}
+
+// Reads an argument of the future output of a transition
+fn future_argument(transition: &Transition, index: usize) -> Option<Plaintext> {
+    transition.outputs().iter().find_map(|output| match output {
+        Output::Future(_, Some(future)) => match future.arguments().get(index)? {
+            Argument::Plaintext(plaintext) => Some(plaintext.clone()),
+            Argument::Future(_) => None,
+        },
+        _ => None,
+    })
+}
+
+fn input_u64(transition: &Transition, index: usize, tvk: Option<&Field>) -> Option<u64> {
+    as_u64(&input_plaintext(transition, index, tvk)?)
+}
+
+fn input_address(transition: &Transition, index: usize, tvk: Option<&Field>) -> Option<Address> {
+    as_address(&input_plaintext(transition, index, tvk)?)
+}
+
+fn future_u64(transition: &Transition, index: usize) -> Option<u64> {
+    as_u64(&future_argument(transition, index)?)
+}
+
+fn future_address(transition: &Transition, index: usize) -> Option<Address> {
+    as_address(&future_argument(transition, index)?)
+}
+
+fn as_u64(plaintext: &Plaintext) -> Option<u64> {
+    match plaintext {
+        Plaintext::Literal(Literal::U64(amount), _) => Some(**amount),
+        _ => None,
+    }
+}
+
+fn as_address(plaintext: &Plaintext) -> Option<Address> {
+    match plaintext {
+        Plaintext::Literal(Literal::Address(address), _) => Some(*address),
+        _ => None,
+    }
+}
+
+// Returns the range of the entries of a page, counting pages from the newest entry
+fn page_range(len: usize, page: usize, page_size: usize) -> Range<usize> {
+    let end = len.saturating_sub(page.saturating_mul(page_size));
+    let start = end.saturating_sub(page_size);
+    start..end
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::account::Account;
+    use crate::backend::NoBlocks;
+
+    #[test]
+    fn test_page_range() {
+        assert_eq!(page_range(45, 0, 20), 25..45);
+        assert_eq!(page_range(45, 1, 20), 5..25);
+        assert_eq!(page_range(45, 2, 20), 0..5);
+        assert_eq!(page_range(45, 3, 20), 0..0);
+        assert_eq!(page_range(0, 0, 20), 0..0);
+    }
+
+    #[test]
+    fn test_transfer_direction() {
+        let account = *crate::account::Account::new().unwrap().address();
+        let other = *crate::account::Account::new().unwrap().address();
+
+        let touch = transfer(&account, Some(account), Some(other), 5).unwrap();
+        assert_eq!(touch.1, Direction::Outgoing);
+        assert_eq!(touch.3, Some(other));
+
+        let touch = transfer(&account, Some(other), Some(account), 5).unwrap();
+        assert_eq!(touch.1, Direction::Incoming);
+        assert_eq!(touch.3, Some(other));
+
+        let touch = transfer(&account, Some(account), Some(account), 5).unwrap();
+        assert_eq!(touch.1, Direction::ToSelf);
+
+        assert!(transfer(&account, Some(other), None, 5).is_none());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let agent = Agent::builder()
+            .with_account(Account::from_seed(1).unwrap())
+            .build();
+        let mut history = agent.history(120);
+        history.entries.push(HistoryEntry {
+            block_height: 100,
+            timestamp: 1_700_000_000,
+            transaction_id: TransactionID::default(),
+            transition_id: TransitionID::default(),
+            program_id: ProgramID::from_str("credits.aleo").unwrap(),
+            function_name: Identifier::from_str("transfer_public").unwrap(),
+            kind: HistoryKind::Transfer,
+            direction: Direction::Outgoing,
+            amount: 5,
+            counterparty: Some(*Account::from_seed(2).unwrap().address()),
+        });
+
+        let path = std::env::temp_dir().join(format!("history_test_{}.json", std::process::id()));
+        history.save(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let loaded = agent.load_history(&path).unwrap();
+        assert_eq!(loaded.next_height(), 120);
+        assert_eq!(loaded.entries(), history.entries());
+
+        // The history of an account is not resumed by another one
+        let other = Agent::builder()
+            .with_account(Account::from_seed(2).unwrap())
+            .build();
+        assert!(other.load_history(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sync_without_blocks() {
+        let agent = Agent::builder()
+            .with_account(Account::from_seed(1).unwrap())
+            .with_backend(Arc::new(NoBlocks))
+            .build();
+        let mut history = agent.history(7);
+        let error = history.sync_to(20).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("The node returned no block at height 7"));
+        assert_eq!(history.next_height(), 7);
+        assert!(history.entries().is_empty());
+    }
+}
@@ -0,0 +1,349 @@
+//! Encrypted keystore files for accounts
+//!
+//! A keystore is a JSON document holding a private key encrypted with AES-256-GCM, under a key
+//! derived from a password with scrypt and a random salt. The address of the account is stored in
+//! clear as a hint, and authenticated as associated data of the encryption.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "address": "aleo1...",
+//!   "crypto": {
+//!     "cipher": "aes-256-gcm",
+//!     "ciphertext": "...",
+//!     "cipherparams": { "nonce": "..." },
+//!     "kdf": "scrypt",
+//!     "kdfparams": { "log_n": 15, "r": 8, "p": 1, "dklen": 32, "salt": "..." }
+//!   }
+//! }
+//! ```
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use anyhow::{anyhow, ensure, Result};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::account::Account;
+
+/// The current version of the keystore format.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+const CIPHER: &str = "aes-256-gcm";
+const KDF: &str = "scrypt";
+const KEY_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+
+// The highest scrypt cost accepted from a keystore file, bounding the time and memory spent on
+// decrypting a file that was crafted or corrupted (128 * r * 2^log_n bytes, 1 GiB at the cap)
+const MAX_LOG_N: u8 = 20;
+const MAX_R: u32 = 8;
+const MAX_P: u32 = 16;
+
+/// An encrypted private key, as stored in a keystore file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub address: String,
+    pub crypto: KeystoreCrypto,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub ciphertext: String,
+    pub cipherparams: CipherParams,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub nonce: String,
+}
+
+/// The scrypt parameters of a keystore.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub dklen: usize,
+    pub salt: String,
+}
+
+impl KdfParams {
+    /// Returns scrypt parameters with a new random salt.
+    ///
+    /// The cost `log_n` = 15, `r` = 8, `p` = 1 is the recommended setting for interactive use.
+    pub fn new(log_n: u8, r: u32, p: u32) -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            log_n,
+            r,
+            p,
+            dklen: KEY_LENGTH,
+            salt: hex::encode(salt),
+        }
+    }
+
+    // Checks that the cost of the parameters is within the bounds accepted from a keystore file
+    fn ensure_bounded(&self) -> Result<()> {
+        ensure!(
+            self.log_n <= MAX_LOG_N && self.r <= MAX_R && self.p <= MAX_P,
+            "Unsupported scrypt cost log_n = {}, r = {}, p = {}: the maximum is log_n = {MAX_LOG_N}, r = {MAX_R}, p = {MAX_P}",
+            self.log_n,
+            self.r,
+            self.p
+        );
+        Ok(())
+    }
+
+    // Derives the encryption key from the password
+    fn derive_key(&self, password: &str) -> Result<[u8; KEY_LENGTH]> {
+        ensure!(
+            self.dklen == KEY_LENGTH,
+            "Unsupported derived key length {}",
+            self.dklen
+        );
+        let params = scrypt::Params::new(self.log_n, self.r, self.p, KEY_LENGTH)
+            .map_err(|error| anyhow!("Invalid scrypt parameters: {error}"))?;
+        let salt = hex::decode(&self.salt)?;
+        let mut key = [0u8; KEY_LENGTH];
+        scrypt::scrypt(password.as_bytes(), &salt, &params, &mut key)
+            .map_err(|error| anyhow!("Failed to derive the keystore key: {error}"))?;
+        Ok(key)
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self::new(15, 8, 1)
+    }
+}
+
+impl Keystore {
+    /// Encrypts the private key of an account with a password.
+    pub fn encrypt(account: &Account, password: &str) -> Result<Self> {
+        Self::encrypt_with_params(account, password, KdfParams::default())
+    }
+
+    /// Encrypts the private key of an account with a password and custom scrypt parameters.
+    pub fn encrypt_with_params(
+        account: &Account,
+        password: &str,
+        kdfparams: KdfParams,
+    ) -> Result<Self> {
+        let address = account.address().to_string();
+        let private_key = Zeroizing::new(account.expose_private_key()?.to_string());
+        let key = Zeroizing::new(kdfparams.derive_key(password)?);
+
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let cipher = Aes256Gcm::new((&*key).into());
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: private_key.as_bytes(),
+                    aad: address.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to encrypt the private key"))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            address,
+            crypto: KeystoreCrypto {
+                cipher: CIPHER.to_string(),
+                ciphertext: hex::encode(ciphertext),
+                cipherparams: CipherParams {
+                    nonce: hex::encode(nonce),
+                },
+                kdf: KDF.to_string(),
+                kdfparams,
+            },
+        })
+    }
+
+    /// Decrypts the account of the keystore with a password.
+    ///
+    /// Keystores whose scrypt cost exceeds `log_n` = 20, `r` = 8 or `p` = 16 are rejected before the
+    /// key is derived.
+    pub fn decrypt(&self, password: &str) -> Result<Account> {
+        ensure!(
+            self.version == KEYSTORE_VERSION,
+            "Unsupported keystore version {}",
+            self.version
+        );
+        ensure!(
+            self.crypto.cipher == CIPHER,
+            "Unsupported keystore cipher {}",
+            self.crypto.cipher
+        );
+        ensure!(
+            self.crypto.kdf == KDF,
+            "Unsupported keystore key derivation function {}",
+            self.crypto.kdf
+        );
+
+        self.crypto.kdfparams.ensure_bounded()?;
+        let key = Zeroizing::new(self.crypto.kdfparams.derive_key(password)?);
+        let nonce = hex::decode(&self.crypto.cipherparams.nonce)?;
+        ensure!(nonce.len() == NONCE_LENGTH, "Invalid keystore nonce");
+        let ciphertext = hex::decode(&self.crypto.ciphertext)?;
+
+        let cipher = Aes256Gcm::new((&*key).into());
+        let private_key = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: self.address.as_bytes(),
+                },
+            )
+            .map_err(|_| {
+                anyhow!("Failed to decrypt the keystore: wrong password or corrupted file")
+            })
+            .map(Zeroizing::new)?;
+
+        let account = Account::from_private_key(std::str::from_utf8(&private_key)?)?;
+        ensure!(
+            account.address().to_string() == self.address,
+            "The keystore address does not match its private key"
+        );
+        Ok(account)
+    }
+
+    /// Reads a keystore from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the keystore to a JSON file.
+    ///
+    /// On Unix, the file is only readable and writable by its owner, mode `0600`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_private_file(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+impl Account {
+    /// Saves the account to an encrypted keystore file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the keystore file.
+    /// * `password` - The password used to encrypt the private key.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use aleo_agent::account::Account;
+    ///
+    /// let account = Account::new().unwrap();
+    /// account.save_keystore("account.json", "password").expect("failed to save keystore");
+    /// let recovered = Account::load_keystore("account.json", "password").expect("failed to load keystore");
+    ///
+    /// assert_eq!(account.address(), recovered.address());
+    /// ```
+    pub fn save_keystore<P: AsRef<Path>>(&self, path: P, password: &str) -> Result<()> {
+        Keystore::encrypt(self, password)?.save(path)
+    }
+
+    /// Loads an account from an encrypted keystore file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the keystore file.
+    /// * `password` - The password used to encrypt the private key.
+    pub fn load_keystore<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        Keystore::load(path)?.decrypt(password)
+    }
+}
+
+// Writes a file only readable and writable by its owner on Unix, mode `0600`
+pub(crate) fn write_private_file<P: AsRef<Path>>(path: P, contents: &[u8]) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files
+        if path.as_ref().exists() {
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let account = Account::new().unwrap();
+        // Use a low cost to keep the test fast
+        let keystore =
+            Keystore::encrypt_with_params(&account, "password", KdfParams::new(4, 8, 1)).unwrap();
+        assert_eq!(keystore.address, account.address().to_string());
+
+        let json = serde_json::to_string(&keystore).unwrap();
+        let keystore: Keystore = serde_json::from_str(&json).unwrap();
+        let recovered = keystore.decrypt("password").unwrap();
+        assert_eq!(recovered.address(), account.address());
+
+        assert!(keystore.decrypt("wrong password").is_err());
+
+        let mut tampered = keystore.clone();
+        tampered.address = Account::new().unwrap().address().to_string();
+        assert!(tampered.decrypt("password").is_err());
+    }
+
+    #[test]
+    fn test_keystore_cost_bounds() {
+        let account = Account::new().unwrap();
+        let keystore =
+            Keystore::encrypt_with_params(&account, "password", KdfParams::new(4, 8, 1)).unwrap();
+        // The bounds are checked before the key is derived, so the test stays fast
+        for (log_n, r, p) in [
+            (21, 8, 1),
+            (4, 9, 1),
+            (4, 8, 17),
+            (u8::MAX, u32::MAX, u32::MAX),
+        ] {
+            let mut costly = keystore.clone();
+            costly.crypto.kdfparams.log_n = log_n;
+            costly.crypto.kdfparams.r = r;
+            costly.crypto.kdfparams.p = p;
+            let error = costly.decrypt("password").unwrap_err();
+            assert!(error.to_string().contains("Unsupported scrypt cost"));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_keystore_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let account = Account::new().unwrap();
+        let keystore =
+            Keystore::encrypt_with_params(&account, "password", KdfParams::new(4, 8, 1)).unwrap();
+        let path = std::env::temp_dir().join(format!("keystore_test_{}.json", std::process::id()));
+        keystore.save(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        let loaded = Keystore::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(loaded.unwrap().address, keystore.address);
+    }
+}
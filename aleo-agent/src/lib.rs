@@ -17,9 +17,9 @@
 //! ```
 //! use aleo_agent::account::Account;
 //! use aleo_agent::agent::{Agent, TransferArgs, TransferType};
-//! use aleo_agent::{Address, MICROCREDITS};
+//! use aleo_agent::address::parse_address;
+//! use aleo_agent::MICROCREDITS;
 //! use anyhow::Result;
-//! use std::str::FromStr;
 //!
 //! // recipient address format: aleo1...
 //! fn transfer_public_balance(recipient_address : &str) -> Result<()> {
@@ -33,7 +33,7 @@
 //!     let public_balance = agent.get_public_balance()?;
 //!     println!("Public Balance : {}", public_balance);
 //!     
-//!     let recipient_address = Address::from_str(recipient_address).expect("Invalid recipient address");
+//!     let recipient_address = parse_address(recipient_address)?;
 //!     // transfer 1 credit to recipient_address
 //!     let transfer_args = TransferArgs::from(
 //!         MICROCREDITS, // transfer 1 credit
@@ -66,14 +66,37 @@ pub use snarkvm::prelude::{
 pub use snarkvm::ledger::store::helpers::memory::BlockMemory;
 
 pub mod account;
+pub mod address;
 pub mod agent;
+pub mod authorization;
+pub mod backend;
+pub mod balance;
 pub mod builder;
 pub mod chain;
+pub mod codegen;
+pub mod compile;
 pub mod deploy;
+#[cfg(feature = "test-utils")]
+pub mod devnet;
+pub mod diff;
+pub mod disclosure;
+pub mod history;
+pub mod keystore;
+pub mod mnemonic;
 pub mod program;
+pub mod receipt;
+pub mod replay;
+pub mod rng;
+pub mod signature;
+pub mod signer;
+pub mod summary;
+pub mod validation;
+pub mod verify;
+pub mod wallet;
 
 // GLOBAL DECLARATIONS
 pub type CurrentNetwork = TestnetV0;
+pub type CurrentAleo = snarkvm::circuit::AleoTestnetV0;
 pub type TransactionID = <CurrentNetwork as Network>::TransactionID;
 pub type CiphertextRecord = Record<CurrentNetwork, Ciphertext>;
 pub type PlaintextRecord = Record<CurrentNetwork, Plaintext>;
@@ -82,7 +105,9 @@ pub type TransitionID = <CurrentNetwork as Network>::TransitionID;
 pub type ProgramID = snarkvm::console::program::ProgramID<CurrentNetwork>;
 pub type Identifier = snarkvm::console::program::Identifier<CurrentNetwork>;
 pub type Value = snarkvm::console::program::Value<CurrentNetwork>;
+pub type Future = snarkvm::console::program::Future<CurrentNetwork>;
 pub type Field = snarkvm::console::types::Field<CurrentNetwork>;
+pub type Scalar = snarkvm::console::types::Scalar<CurrentNetwork>;
 pub type Ciphertext = snarkvm::console::program::Ciphertext<CurrentNetwork>;
 pub type Plaintext = snarkvm::console::program::Plaintext<CurrentNetwork>;
 pub type PrivateKey = snarkvm::console::account::PrivateKey<CurrentNetwork>;
@@ -93,12 +118,15 @@ pub type Group = snarkvm::console::account::Group<CurrentNetwork>;
 pub type Query = snarkvm::ledger::query::Query<CurrentNetwork, BlockMemory<CurrentNetwork>>;
 pub type Block = snarkvm::ledger::Block<CurrentNetwork>;
 pub type Transaction = snarkvm::ledger::Transaction<CurrentNetwork>;
+pub type Transition = snarkvm::ledger::block::Transition<CurrentNetwork>;
 pub type ConfirmedTransaction = snarkvm::ledger::ConfirmedTransaction<CurrentNetwork>;
 pub type Transactions = snarkvm::ledger::Transactions<CurrentNetwork>;
 pub type ConsensusMemory = snarkvm::ledger::store::helpers::memory::ConsensusMemory<CurrentNetwork>;
 pub type ConsensusStore = snarkvm::ledger::store::ConsensusStore<CurrentNetwork, ConsensusMemory>;
 pub type VM = snarkvm::synthesizer::VM<CurrentNetwork, ConsensusMemory>;
 pub type Program = snarkvm::synthesizer::Program<CurrentNetwork>;
+pub type Process = snarkvm::synthesizer::process::Process<CurrentNetwork>;
+pub type Authorization = snarkvm::synthesizer::process::Authorization<CurrentNetwork>;
 pub type Package = snarkvm::package::Package<CurrentNetwork>;
 
 pub const DEFAULT_BASE_URL: &str = "https://api.explorer.aleo.org/v1";
@@ -0,0 +1,178 @@
+//! Deterministic accounts from BIP-39 mnemonic phrases
+//!
+//! A mnemonic phrase is turned into a seed with BIP-39, then an account is derived from the seed
+//! along a hardened path with SLIP-10 (ed25519 curve). The default path of the account at `index`
+//! is `m/44'/683'/{index}'/0'`, 683 being the coin type of Aleo. The 32-byte derived key is reduced
+//! into a field element and used as the seed of the private key.
+//!
+//! The derivation is checked against the SLIP-10 test vectors only. Wallets are free to derive keys
+//! from a mnemonic differently, so the accounts derived here are not guaranteed to match the
+//! accounts a wallet derives from the same phrase.
+//!
+//! Compatibility with existing Aleo wallets is not provided: it needs a verified test vector of a
+//! wallet, a phrase with the account the wallet restores from it, which this crate does not have.
+//! [Account::from_mnemonic_path] takes the path a wallet uses, but the reduction of the derived key
+//! into a private key may still differ.
+//!
+//! # Example
+//! ```
+//! use aleo_agent::account::Account;
+//! use aleo_agent::mnemonic::generate_mnemonic;
+//!
+//! let phrase = generate_mnemonic(24).unwrap();
+//! let first = Account::from_mnemonic(&phrase, "", 0).unwrap();
+//! let second = Account::from_mnemonic(&phrase, "", 1).unwrap();
+//!
+//! assert_eq!(first.address(), Account::from_mnemonic(&phrase, "", 0).unwrap().address());
+//! assert_ne!(first.address(), second.address());
+//! ```
+
+use anyhow::{anyhow, bail, ensure, Result};
+use bip39::{Language, Mnemonic};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::account::Account;
+
+use super::*;
+
+/// The BIP-44 coin type of Aleo.
+pub const ALEO_COIN_TYPE: u32 = 683;
+
+const HARDENED: u32 = 0x8000_0000;
+const CURVE: &[u8] = b"ed25519 seed";
+
+/// Generates a new random English mnemonic phrase of 12, 15, 18, 21 or 24 words.
+pub fn generate_mnemonic(word_count: usize) -> Result<Zeroizing<String>> {
+    let mnemonic = Mnemonic::generate_in(Language::English, word_count)
+        .map_err(|error| anyhow!("Failed to generate a mnemonic: {error}"))?;
+    Ok(Zeroizing::new(mnemonic.to_string()))
+}
+
+/// Returns the default derivation path of the account at `index`.
+pub fn derivation_path(index: u32) -> String {
+    format!("m/44'/{ALEO_COIN_TYPE}'/{index}'/0'")
+}
+
+impl Account {
+    /// Derives the account at `index` from a mnemonic phrase, along the path
+    /// `m/44'/683'/{index}'/0'`.
+    ///
+    /// # Arguments
+    /// * `phrase` - The English BIP-39 mnemonic phrase.
+    /// * `passphrase` - The optional BIP-39 passphrase, empty if unused.
+    /// * `index` - The index of the account.
+    pub fn from_mnemonic(phrase: &str, passphrase: &str, index: u32) -> Result<Self> {
+        Self::from_mnemonic_path(phrase, passphrase, &derivation_path(index))
+    }
+
+    /// Derives an account from a mnemonic phrase along a custom hardened derivation path, such as
+    /// `m/44'/683'/0'/0'`.
+    pub fn from_mnemonic_path(phrase: &str, passphrase: &str, path: &str) -> Result<Self> {
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase)
+            .map_err(|error| anyhow!("Invalid mnemonic phrase: {error}"))?;
+        let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+        let key = derive_key(seed.as_ref(), &parse_path(path)?);
+        let private_key = PrivateKey::try_from(Field::from_bytes_le_mod_order(key.as_ref()))?;
+        let view_key = ViewKey::try_from(&private_key)?;
+        let address = Address::try_from(&private_key)?;
+        Ok(Account::from_keys(private_key, view_key, address))
+    }
+}
+
+// Parses a derivation path of hardened indexes into the indexes
+fn parse_path(path: &str) -> Result<Vec<u32>> {
+    let mut segments = path.split('/');
+    ensure!(
+        segments.next() == Some("m"),
+        "Derivation path '{path}' must start with 'm'"
+    );
+    segments
+        .map(|segment| {
+            let Some(index) = segment.strip_suffix('\'') else {
+                bail!("Derivation path '{path}' must only contain hardened indexes");
+            };
+            let index = index
+                .parse::<u32>()
+                .map_err(|_| anyhow!("Invalid index '{segment}' in derivation path '{path}'"))?;
+            ensure!(
+                index < HARDENED,
+                "Index '{segment}' of derivation path '{path}' is too large"
+            );
+            Ok(index)
+        })
+        .collect()
+}
+
+// Derives the private key of a hardened path from a seed with SLIP-10
+fn derive_key(seed: &[u8], path: &[u32]) -> Zeroizing<[u8; 32]> {
+    let (mut key, mut chain_code) = hmac_sha512(CURVE, &[seed]);
+    for index in path {
+        (key, chain_code) = hmac_sha512(
+            chain_code.as_ref(),
+            &[&[0u8], key.as_ref(), &(index | HARDENED).to_be_bytes()],
+        );
+    }
+    key
+}
+
+// Splits HMAC-SHA512 of the data into a key and a chain code
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> (Zeroizing<[u8; 32]>, Zeroizing<[u8; 32]>) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for data in data {
+        mac.update(data);
+    }
+    let mut output = mac.finalize().into_bytes();
+    let mut left = Zeroizing::new([0u8; 32]);
+    let mut right = Zeroizing::new([0u8; 32]);
+    left.copy_from_slice(&output[..32]);
+    right.copy_from_slice(&output[32..]);
+    output.as_mut_slice().zeroize();
+    (left, right)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slip10_vectors() {
+        // SLIP-10 test vector 1 for ed25519
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        assert_eq!(
+            hex::encode(*derive_key(&seed, &[])),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            hex::encode(*derive_key(&seed, &[0, 1])),
+            "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2"
+        );
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path(&derivation_path(3)).unwrap(),
+            vec![44, ALEO_COIN_TYPE, 3, 0]
+        );
+        assert!(parse_path("44'/683'").is_err());
+        assert!(parse_path("m/44'/683").is_err());
+        assert!(parse_path("m/2147483648'").is_err());
+    }
+
+    #[test]
+    fn test_from_mnemonic() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let account = Account::from_mnemonic(phrase, "", 0).unwrap();
+        let same = Account::from_mnemonic_path(phrase, "", "m/44'/683'/0'/0'").unwrap();
+        assert_eq!(account.address(), same.address());
+        assert_ne!(
+            account.address(),
+            Account::from_mnemonic(phrase, "passphrase", 0)
+                .unwrap()
+                .address()
+        );
+        assert!(Account::from_mnemonic("abandon abandon", "", 0).is_err());
+    }
+}
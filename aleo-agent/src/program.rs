@@ -11,7 +11,12 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use crate::agent::Agent;
-use anyhow::{anyhow, bail, ensure, Error, Result};
+use crate::compile;
+use crate::diff::ProgramDiff;
+use crate::receipt::ExecutionReceipt;
+use crate::rng::CryptoRngCore;
+use crate::validation::{validate_inputs, InputError};
+use anyhow::{anyhow, bail, ensure, Result};
 use indexmap::IndexMap;
 
 use super::*;
@@ -49,8 +54,13 @@ impl<'agent> ProgramManager<'agent> {
     /// * `priority_fee` - The priority fee to pay for the transaction
     /// * `fee_record` - The plaintext record to pay for the transaction fee. If None, the fee will be paid through the account's public balance
     ///
+    /// The inputs are validated against the function signature before proving, see
+    /// [validate_inputs](crate::validation::validate_inputs).
+    ///
     /// # Returns
-    /// The transaction ID of the execution transaction
+    /// The [ExecutionReceipt] of the transaction, with the outputs decrypted using the agent's view key,
+    /// see [Agent::view_key]. With an external signer disclosing no view key, the transaction is still
+    /// broadcast and its private outputs and records are left encrypted in the receipt
     ///
     /// # Example
     /// ```ignore
@@ -60,7 +70,8 @@ impl<'agent> ProgramManager<'agent> {
     ///
     /// // Execute the main function of the xxx.aleo program with inputs 1, 2, 3; priority fee 100; and no fee record
     /// // The fee will be paid through account's public balance
-    /// let tx_id = pm.execute_program("main", vec![1, 2, 3].into_iter(), 100, None).expect("Failed to execute program");
+    /// let receipt = pm.execute_program("main", vec![1, 2, 3].into_iter(), 100, None).expect("Failed to execute program");
+    /// let outputs = receipt.outputs().expect("Failed to decrypt outputs");
     /// ```
     pub fn execute_program(
         &self,
@@ -68,32 +79,55 @@ impl<'agent> ProgramManager<'agent> {
         inputs: impl ExactSizeIterator<Item = impl TryInto<Value>>,
         priority_fee: u64,
         fee_record: Option<PlaintextRecord>,
-    ) -> Result<String> {
+    ) -> Result<ExecutionReceipt> {
+        self.agent().with_rng(|rng| {
+            self.execute_program_with_rng(function, inputs, priority_fee, fee_record, rng)
+        })
+    }
+
+    /// Executes a program function, drawing the randomness of the transaction from `rng`.
+    ///
+    /// See [execute_program](ProgramManager::execute_program) for the arguments.
+    pub fn execute_program_with_rng(
+        &self,
+        function: &str,
+        inputs: impl ExactSizeIterator<Item = impl TryInto<Value>>,
+        priority_fee: u64,
+        fee_record: Option<PlaintextRecord>,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<ExecutionReceipt> {
         // Check program and function have valid names
         let function_id: Identifier =
             Identifier::from_str(function).map_err(|_| anyhow!("Invalid function name"))?;
-
         // Get the program from chain, error if it doesn't exist
-        let program = Self::get_program_from_chain(self.program_id())?;
-
-        // Initialize an RNG and query object for the transaction
-        let rng = &mut rand::thread_rng();
-        let query = Query::from(self.agent().base_url());
+        let program = self.agent().get_program(self.program_id())?;
 
-        let vm = Self::initialize_vm(&program)?;
+        // Check the inputs against the function signature before any proving begins
+        let inputs = inputs
+            .enumerate()
+            .map(|(index, input)| input.try_into().map_err(|_| InputError::Parse { index }))
+            .collect::<Result<Vec<Value>, InputError>>()?;
+        validate_inputs(
+            &program,
+            &function_id,
+            &inputs,
+            &self.agent().signer()?.address(),
+        )?;
 
-        let transaction = vm.execute(
-            self.agent().account().private_key(),
-            (program.id(), function_id),
-            inputs,
-            fee_record,
+        let vm = self.initialize_vm(&program)?;
+        let transaction = self.agent().create_execute_transaction(
+            &vm,
+            program.id(),
+            &function_id,
+            &inputs,
             priority_fee,
-            Some(query),
+            fee_record,
             rng,
         )?;
 
         // Broadcast the execution transaction to the network
-        self.agent().broadcast_transaction(&transaction)
+        let response = self.agent().broadcast_transaction(&transaction)?;
+        ExecutionReceipt::new(&transaction, self.agent().view_key().ok(), response)
     }
 
     /// Execute a program function on the Aleo Network with a priority fee and no fee record
@@ -119,9 +153,13 @@ impl<'agent> ProgramManager<'agent> {
         block_heights: Range<u32>,
         unspent_only: bool,
     ) -> Result<Vec<(Field, CiphertextRecord)>> {
-        let private_key = self.agent().account().private_key();
+        // Serial numbers are only needed to filter out spent records
+        let private_key = match unspent_only {
+            true => Some(self.agent().serial_number_key()?),
+            false => None,
+        };
         // Prepare the view key.
-        let view_key = self.agent().account().view_key();
+        let view_key = self.agent().view_key()?;
         // Compute the x-coordinate of the address.
         let address_x_coordinate = view_key.to_address().to_x_coordinate();
 
@@ -148,22 +186,20 @@ impl<'agent> ProgramManager<'agent> {
                 .flat_map(|transition| transition.into_records())
                 .filter_map(|(commitment, record)| {
                     if record.is_owner_with_address_x_coordinate(view_key, &address_x_coordinate) {
-                        if unspent_only {
+                        if let Some(private_key) = private_key {
                             let sn =
                                 CiphertextRecord::serial_number(*private_key, commitment).ok()?;
-                            if self
-                                .agent()
-                                .find_transition_id_by_input_or_output_id(sn)
-                                .is_err()
-                            {
-                                return Some((commitment, record));
-                            }
+                            return match self.agent().is_spent(sn) {
+                                Ok(spent) => (!spent).then_some(Ok((commitment, record))),
+                                Err(error) => Some(Err(error)),
+                            };
                         } else {
-                            return Some((commitment, record));
+                            return Some(Ok((commitment, record)));
                         }
                     };
                     None
-                });
+                })
+                .collect::<Result<Vec<_>>>()?;
             records.extend(_records);
         }
 
@@ -191,13 +227,8 @@ impl<'agent> ProgramManager<'agent> {
         let key = key.try_into().map_err(|_| anyhow!("Invalid key"))?;
         let program_id = self.program_id();
         // Perform the request.
-        let url = format!(
-            "{}/{}/program/{}/mapping/{mapping_name}/{key}",
-            self.agent().base_url(),
-            self.agent().network(),
-            program_id.to_string(),
-        );
-        match self.agent().client().get(&url).call()?.into_json() {
+        let path = format!("/program/{program_id}/mapping/{mapping_name}/{key}");
+        match self.agent().get_json(&path) {
             Ok(transition_id) => Ok(transition_id),
             Err(error) => bail!("Failed to parse transition ID: {error}"),
         }
@@ -208,22 +239,93 @@ impl<'agent> ProgramManager<'agent> {
         // Prepare the program ID.
         let program_id = self.program_id();
         // Perform the request.
-        let url = format!(
-            "{}/{}/program/{}/mappings",
-            self.agent().base_url(),
-            self.agent().network(),
-            program_id.to_string()
-        );
-        match self.agent().client().get(&url).call()?.into_json() {
+        let path = format!("/program/{program_id}/mappings");
+        match self.agent().get_json(&path) {
             Ok(program_mappings) => Ok(program_mappings),
             Err(error) => bail!("Failed to parse program {program_id}: {error}"),
         }
     }
 }
 
+// deployment information
+impl<'agent> ProgramManager<'agent> {
+    /// Get the transaction that deployed the program.
+    pub fn get_deployment_transaction(&self) -> Result<Transaction> {
+        let transaction_id = self
+            .agent()
+            .find_deployment_transaction_id(self.program_id())?;
+        self.agent().get_transaction(&transaction_id.to_string())
+    }
+
+    /// Get the edition of the deployed program.
+    pub fn get_program_edition(&self) -> Result<u16> {
+        match self.get_deployment_transaction()? {
+            Transaction::Deploy(_, _, deployment, _) => Ok(deployment.edition()),
+            _ => bail!("Transaction is not a deployment of {}", self.program_id()),
+        }
+    }
+
+    /// Get the address of the account that deployed the program.
+    pub fn get_program_owner(&self) -> Result<Address> {
+        match self.get_deployment_transaction()? {
+            Transaction::Deploy(_, owner, _, _) => Ok(*owner.address()),
+            _ => bail!("Transaction is not a deployment of {}", self.program_id()),
+        }
+    }
+
+    /// Compare a local version of the program with the one deployed on chain.
+    ///
+    /// # Arguments
+    /// * `local_program` - The local version of the program
+    ///
+    /// # Returns
+    /// The changes from the deployed program to the local program
+    pub fn diff(&self, local_program: &Program) -> Result<ProgramDiff> {
+        ensure!(
+            local_program.id() == self.program_id(),
+            "Cannot compare {} with {}",
+            local_program.id(),
+            self.program_id()
+        );
+        let onchain_program = self.agent().get_program(self.program_id())?;
+        Ok(ProgramDiff::new(&onchain_program, local_program))
+    }
+}
+
+impl Agent {
+    /// Fetches the imports of a program from the agent's node, recursively.
+    ///
+    /// # Returns
+    /// The imported programs by ID, every program coming after its own imports
+    pub fn get_import_programs(&self, program: &Program) -> Result<IndexMap<ProgramID, Program>> {
+        let mut imports = IndexMap::new();
+        self.resolve_imports(program, &mut imports)?;
+        Ok(imports)
+    }
+
+    // Adds the imports of a program to `imports` in depth-first order
+    fn resolve_imports(
+        &self,
+        program: &Program,
+        imports: &mut IndexMap<ProgramID, Program>,
+    ) -> Result<()> {
+        for import_id in program.imports().keys() {
+            if imports.contains_key(import_id) {
+                continue;
+            }
+            let import = self.get_program(import_id)?;
+            self.resolve_imports(&import, imports)?;
+            imports.insert(*import_id, import);
+        }
+        Ok(())
+    }
+}
+
 // program associated functions
 impl<'agent> ProgramManager<'agent> {
     /// Get a program from the network by its ID. This method will return an error if it does not exist.
+    ///
+    /// The program is fetched from the default network, [Agent::get_program] uses the agent's node.
     pub fn get_program_from_chain(program_id: &ProgramID) -> Result<Program> {
         let client = ureq::Agent::new();
         // Perform the request.
@@ -239,6 +341,9 @@ impl<'agent> ProgramManager<'agent> {
 
     /// Resolve imports of a program in a depth-first-search order from program source code
     ///
+    /// The imports are fetched from the default network, [Agent::get_import_programs] uses the
+    /// agent's node.
+    ///
     /// # Arguments
     /// * `program` - The program to resolve imports for
     ///
@@ -284,26 +389,27 @@ impl<'agent> ProgramManager<'agent> {
         Ok(package.program().clone())
     }
 
-    /// Initialize a SnarkVM instance with a program and its imports
-    fn initialize_vm(program: &Program) -> Result<VM> {
-        // Create an ephemeral SnarkVM to store the programs
-        // Initialize an RNG and query object for the transaction
-        let store = ConsensusStore::open(None)?;
-        let vm = VM::from(store)?;
-
-        // Resolve imports
-        let credits_id = ProgramID::from_str("credits.aleo")?;
-        Self::get_import_programs_from_chain(program)?
-            .iter()
-            .try_for_each(|(_, import)| {
-                if import.id() != &credits_id {
-                    vm.process().write().add_program(import)?
-                }
-                Ok::<_, Error>(())
-            })?;
+    /// Load a program from Aleo instructions
+    ///
+    /// The program is parsed and type-checked in-process, see [compile](crate::compile) for loading
+    /// a whole directory of `.aleo` files.
+    ///
+    /// # Arguments
+    /// * source - The Aleo instructions of the program.
+    /// Imports other than `credits.aleo` are fetched from the network.
+    pub fn load_program_from_source(source: &str) -> Result<Program> {
+        let program = compile::parse_program(source)?;
+        let imports = Self::get_import_programs_from_chain(&program)?
+            .into_values()
+            .collect::<Vec<_>>();
+        compile::check_program(source, &program, &imports)?;
+        Ok(program)
+    }
 
-        // If the initialization is for an execution, add the program. Otherwise, don't add it as
-        // it will be added during the deployment process
+    /// Initialize a SnarkVM instance with a program and its imports, fetched from the agent's node
+    pub(crate) fn initialize_vm(&self, program: &Program) -> Result<VM> {
+        let vm = self.agent().initialize_vm(program)?;
+        // The initialization is for an execution, add the program
         vm.process().write().add_program(program)?;
         Ok(vm)
     }
@@ -0,0 +1,347 @@
+//! Receipts of executed transactions
+//!
+//! An [ExecutionReceipt] is built from the transaction created locally, so the outputs of every
+//! transition are available as soon as the transaction is broadcast. Private outputs and records
+//! are decrypted with the view key of the account that signed the execution, and are left
+//! encrypted when no view key is available.
+
+use anyhow::{bail, Result};
+use snarkvm::console::program::compute_function_id;
+use snarkvm::console::types::U16;
+use snarkvm::ledger::block::Output;
+
+use crate::disclosure::transition_view_key;
+
+use super::*;
+
+/// An output of a transition.
+#[derive(Clone, Debug)]
+pub enum TransitionOutput {
+    /// A constant or public output.
+    Public(Plaintext),
+    /// A private output, decrypted with the transition view key.
+    Private(Plaintext),
+    /// A private output that could not be decrypted.
+    Ciphertext(Ciphertext),
+    /// A record owned by the account, with its commitment.
+    Record(Field, PlaintextRecord),
+    /// A record owned by another address, or decoded without a view key, with its commitment.
+    ForeignRecord(Field, CiphertextRecord),
+    /// A record of another program, hidden by its hash.
+    ExternalRecord(Field),
+    /// A future, evaluated by the network during finalize.
+    Future(Future),
+}
+
+impl TransitionOutput {
+    /// Returns the output as a [Value], if it is known in plaintext.
+    pub fn to_value(&self) -> Option<Value> {
+        match self {
+            TransitionOutput::Public(plaintext) | TransitionOutput::Private(plaintext) => {
+                Some(Value::Plaintext(plaintext.clone()))
+            }
+            TransitionOutput::Record(_, record) => Some(Value::Record(record.clone())),
+            TransitionOutput::Future(future) => Some(Value::Future(future.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// The outputs of a single transition.
+#[derive(Clone, Debug)]
+pub struct TransitionReceipt {
+    id: TransitionID,
+    program_id: ProgramID,
+    function_name: Identifier,
+    outputs: Vec<TransitionOutput>,
+}
+
+impl TransitionReceipt {
+    /// Decodes the outputs of a transition with the view key of its signer.
+    ///
+    /// Records are decrypted if the view key belongs to their owner.
+    ///
+    /// # Errors
+    /// If the transition was not signed by the view key, whose private outputs would not decrypt.
+    pub fn from_transition(transition: &Transition, view_key: &ViewKey) -> Result<Self> {
+        let tvk = transition_view_key(transition, view_key)?;
+        Self::decode(transition, Some((&tvk, view_key)))
+    }
+
+    /// Decodes the outputs of a transition without a view key.
+    ///
+    /// Private outputs are left as [TransitionOutput::Ciphertext] and records as
+    /// [TransitionOutput::ForeignRecord].
+    pub fn without_view_key(transition: &Transition) -> Result<Self> {
+        Self::decode(transition, None)
+    }
+
+    // Decodes the outputs of a transition with its transition view key and the view key of its signer
+    fn decode(transition: &Transition, keys: Option<(&Field, &ViewKey)>) -> Result<Self> {
+        let outputs = transition
+            .outputs()
+            .iter()
+            .enumerate()
+            .map(|(index, output)| decode_output(transition, keys, index, output))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            id: *transition.id(),
+            program_id: *transition.program_id(),
+            function_name: *transition.function_name(),
+            outputs,
+        })
+    }
+
+    pub fn id(&self) -> &TransitionID {
+        &self.id
+    }
+
+    pub fn program_id(&self) -> &ProgramID {
+        &self.program_id
+    }
+
+    pub fn function_name(&self) -> &Identifier {
+        &self.function_name
+    }
+
+    pub fn outputs(&self) -> &[TransitionOutput] {
+        &self.outputs
+    }
+
+    /// Returns the outputs as values, failing if one of them could not be decrypted.
+    pub fn values(&self) -> Result<Vec<Value>> {
+        self.outputs
+            .iter()
+            .enumerate()
+            .map(|(index, output)| match output.to_value() {
+                Some(value) => Ok(value),
+                None => bail!(
+                    "Output #{index} of '{}/{}' is not visible to this account",
+                    self.program_id,
+                    self.function_name
+                ),
+            })
+            .collect()
+    }
+}
+
+/// The receipt of a program execution.
+#[derive(Clone, Debug)]
+pub struct ExecutionReceipt {
+    transaction_id: TransactionID,
+    transitions: Vec<TransitionReceipt>,
+    records: Vec<(Field, PlaintextRecord)>,
+    fee: u64,
+    response: String,
+}
+
+impl ExecutionReceipt {
+    /// Builds the receipt of an execution transaction.
+    ///
+    /// # Arguments
+    /// * `transaction` - The execution transaction
+    /// * `view_key` - The view key of the account that signed the execution. Without it, private
+    ///   outputs and records are left encrypted, see [TransitionReceipt::without_view_key]
+    /// * `response` - The response of the node to the broadcast of the transaction
+    ///
+    /// # Errors
+    /// If the execution was not signed by the view key, see [TransitionReceipt::from_transition].
+    pub fn new(
+        transaction: &Transaction,
+        view_key: Option<&ViewKey>,
+        response: String,
+    ) -> Result<Self> {
+        let Transaction::Execute(_, execution, fee) = transaction else {
+            bail!("Transaction {} is not an execution", transaction.id());
+        };
+
+        let transitions = execution
+            .transitions()
+            .map(|transition| match view_key {
+                Some(view_key) => TransitionReceipt::from_transition(transition, view_key),
+                None => TransitionReceipt::without_view_key(transition),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Collect the new records of the account, including the change of a private fee
+        let mut records = transitions
+            .iter()
+            .flat_map(|transition| transition.outputs().iter())
+            .filter_map(|output| match output {
+                TransitionOutput::Record(commitment, record) => Some((*commitment, record.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if let (Some(fee), Some(view_key)) = (fee, view_key) {
+            records.extend(
+                fee.records()
+                    .filter(|(_, record)| record.is_owner(view_key))
+                    .map(|(commitment, record)| Ok((*commitment, record.decrypt(view_key)?)))
+                    .collect::<Result<Vec<_>>>()?,
+            );
+        }
+
+        Ok(Self {
+            transaction_id: transaction.id(),
+            transitions,
+            records,
+            fee: *transaction.fee_amount()?,
+            response,
+        })
+    }
+
+    pub fn transaction_id(&self) -> &TransactionID {
+        &self.transaction_id
+    }
+
+    /// Returns the transitions of the execution, the called function being the last one.
+    pub fn transitions(&self) -> &[TransitionReceipt] {
+        &self.transitions
+    }
+
+    /// Returns the records created for the account, with their commitments.
+    pub fn records(&self) -> &[(Field, PlaintextRecord)] {
+        &self.records
+    }
+
+    /// Returns the fee paid in microcredits.
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    /// Returns the response of the node to the broadcast.
+    pub fn response(&self) -> &str {
+        &self.response
+    }
+
+    /// Returns the outputs of the called function.
+    pub fn outputs(&self) -> Result<Vec<Value>> {
+        match self.transitions.last() {
+            Some(transition) => transition.values(),
+            None => bail!("Execution {} has no transitions", self.transaction_id),
+        }
+    }
+}
+
+// Decodes an output of a transition, decrypting it where the keys allow
+fn decode_output(
+    transition: &Transition,
+    keys: Option<(&Field, &ViewKey)>,
+    index: usize,
+    output: &Output<CurrentNetwork>,
+) -> Result<TransitionOutput> {
+    Ok(match output {
+        Output::Constant(_, Some(plaintext)) | Output::Public(_, Some(plaintext)) => {
+            TransitionOutput::Public(plaintext.clone())
+        }
+        Output::Private(_, Some(ciphertext)) => match keys {
+            Some((tvk, _)) => {
+                let output_view_key = output_view_key(transition, tvk, index)?;
+                match ciphertext.decrypt_symmetric(output_view_key) {
+                    Ok(plaintext) => TransitionOutput::Private(plaintext),
+                    Err(_) => TransitionOutput::Ciphertext(ciphertext.clone()),
+                }
+            }
+            None => TransitionOutput::Ciphertext(ciphertext.clone()),
+        },
+        Output::Record(commitment, _, Some(record)) => match keys {
+            Some((_, view_key)) if record.is_owner(view_key) => {
+                TransitionOutput::Record(*commitment, record.decrypt(view_key)?)
+            }
+            _ => TransitionOutput::ForeignRecord(*commitment, record.clone()),
+        },
+        Output::ExternalRecord(hash) => TransitionOutput::ExternalRecord(*hash),
+        Output::Future(_, Some(future)) => TransitionOutput::Future(future.clone()),
+        _ => bail!(
+            "Output #{index} of transition {} is missing its value",
+            transition.id()
+        ),
+    })
+}
+
+// Computes the symmetric key of a private output from the transition view key
+pub(crate) fn output_view_key(transition: &Transition, tvk: &Field, index: usize) -> Result<Field> {
+    // Outputs are indexed after the inputs of the transition
+    io_view_key(transition, tvk, transition.inputs().len() + index)
+}
+
+// Computes the symmetric key of a private input from the transition view key
+pub(crate) fn input_view_key(transition: &Transition, tvk: &Field, index: usize) -> Result<Field> {
+    io_view_key(transition, tvk, index)
+}
+
+fn io_view_key(transition: &Transition, tvk: &Field, index: usize) -> Result<Field> {
+    let function_id = compute_function_id(
+        &U16::new(CurrentNetwork::ID),
+        transition.program_id(),
+        transition.function_name(),
+    )?;
+    let index = Field::from_u16(u16::try_from(index)?);
+    CurrentNetwork::hash_psd4(&[function_id, *tvk, index])
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    use super::*;
+
+    const PROGRAM: &str = r"
+program receipt_test.aleo;
+
+function main:
+    input r0 as u32.public;
+    input r1 as u32.private;
+    add r0 r1 into r2;
+    output r2 as u32.public;
+    output r2 as u32.private;
+";
+
+    #[test]
+    fn test_decode_outputs() {
+        let rng = &mut ChaChaRng::seed_from_u64(0);
+        let private_key = PrivateKey::new(rng).unwrap();
+        let program = Program::from_str(PROGRAM).unwrap();
+        let mut process = Process::load().unwrap();
+        process.add_program(&program).unwrap();
+        let inputs = [
+            Value::from_str("1u32").unwrap(),
+            Value::from_str("2u32").unwrap(),
+        ];
+        let authorization = process
+            .authorize::<CurrentAleo, _>(&private_key, program.id(), "main", inputs.iter(), rng)
+            .unwrap();
+        let (_, trace) = process
+            .execute::<CurrentAleo, _>(authorization, rng)
+            .unwrap();
+        let transitions = trace.transitions();
+        let transition = &transitions[0];
+
+        let view_key = ViewKey::try_from(&private_key).unwrap();
+        let receipt = TransitionReceipt::from_transition(transition, &view_key).unwrap();
+        let three = Plaintext::from_str("3u32").unwrap();
+        assert!(
+            matches!(&receipt.outputs()[0], TransitionOutput::Public(value) if value == &three)
+        );
+        assert!(
+            matches!(&receipt.outputs()[1], TransitionOutput::Private(value) if value == &three)
+        );
+        assert_eq!(receipt.values().unwrap().len(), 2);
+
+        let other = ViewKey::try_from(&PrivateKey::new(rng).unwrap()).unwrap();
+        assert!(TransitionReceipt::from_transition(transition, &other).is_err());
+
+        let receipt = TransitionReceipt::without_view_key(transition).unwrap();
+        assert!(
+            matches!(&receipt.outputs()[0], TransitionOutput::Public(value) if value == &three)
+        );
+        assert!(matches!(
+            &receipt.outputs()[1],
+            TransitionOutput::Ciphertext(_)
+        ));
+        assert!(receipt.values().is_err());
+    }
+}
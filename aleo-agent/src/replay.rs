@@ -0,0 +1,545 @@
+//! Reconstruction of program mappings by replaying blocks
+//!
+//! Nodes only serve mapping values by key, and the finalize operations recorded in blocks only hold
+//! hashes of the keys and values they touch. The [MappingReplay] rebuilds the mappings of every
+//! program by adding the blocks, from genesis, to an in-memory VM, which re-runs their finalize
+//! logic and checks the result against the finalize operations of each block.
+//!
+//! Replaying is slow, and every block up to the target height must be fetched: the replay is meant
+//! for batch jobs such as reconciliations, not for interactive queries. The [MappingHistory] keeps
+//! the changes of chosen keys along the way, to answer queries at past heights from its cache.
+//!
+//! # Example
+//! ```ignore
+//! use aleo_agent::agent::Agent;
+//!
+//! let agent = Agent::default();
+//! let pm = agent.program("token.aleo").unwrap();
+//! let snapshot = pm.export_mappings(120_000).unwrap();
+//! std::fs::write("token.csv", snapshot.to_csv()).unwrap();
+//! ```
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, ensure, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::Agent;
+use crate::backend::is_not_found;
+use crate::program::ProgramManager;
+
+use super::*;
+
+// The number of blocks fetched per request
+const BATCH_SIZE: u32 = 50;
+
+/// An in-memory VM advanced block by block from genesis.
+pub struct MappingReplay<'agent> {
+    agent: &'agent Agent,
+    vm: VM,
+    next_height: u32,
+}
+
+impl Agent {
+    /// Creates an empty mapping replay, to be advanced with [MappingReplay::sync_to].
+    pub fn mapping_replay(&self) -> Result<MappingReplay> {
+        Ok(MappingReplay {
+            agent: self,
+            vm: VM::from(ConsensusStore::open(None)?)?,
+            next_height: 0,
+        })
+    }
+}
+
+impl<'agent> MappingReplay<'agent> {
+    /// Returns the height of the last replayed block, if any.
+    pub fn height(&self) -> Option<u32> {
+        self.next_height.checked_sub(1)
+    }
+
+    /// Replays the blocks up to the latest block.
+    pub fn sync(&mut self) -> Result<()> {
+        let latest_height = self.agent.get_latest_block_height()?;
+        self.sync_to(latest_height)
+    }
+
+    /// Replays the blocks up to `height` (inclusive).
+    ///
+    /// A replay cannot go back: `height` must not be lower than the last replayed height.
+    pub fn sync_to(&mut self, height: u32) -> Result<()> {
+        self.sync_to_with(height, |_, _| Ok(()))
+    }
+
+    /// Replays the blocks up to `height` (inclusive), calling `on_block` after each block with the
+    /// replay at the height of the block.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut replay = agent.mapping_replay().unwrap();
+    /// replay
+    ///     .sync_to_with(1_000, |replay, block| {
+    ///         let supply = replay.mapping_value(&program_id, &mapping, &key)?;
+    ///         println!("{}: {supply:?}", block.height());
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn sync_to_with(
+        &mut self,
+        height: u32,
+        mut on_block: impl FnMut(&Self, &Block) -> Result<()>,
+    ) -> Result<()> {
+        ensure!(
+            self.height().map_or(true, |replayed| replayed <= height),
+            "The replay is already at height {}, past height {height}",
+            self.next_height - 1
+        );
+        while self.next_height <= height {
+            let start_height = self.next_height;
+            let end_height = (height + 1).min(start_height + BATCH_SIZE);
+            for block in self.agent.get_blocks_in_range(start_height, end_height)? {
+                self.apply_block(&block)?;
+                on_block(self, &block)?;
+            }
+            ensure!(
+                self.next_height > start_height,
+                "The node returned no block at height {start_height}"
+            );
+        }
+        Ok(())
+    }
+
+    /// Replays the next block.
+    pub fn apply_block(&mut self, block: &Block) -> Result<()> {
+        ensure!(
+            block.height() == self.next_height,
+            "Expected block {}, found block {}",
+            self.next_height,
+            block.height()
+        );
+        self.vm.add_next_block(block)?;
+        self.next_height += 1;
+        Ok(())
+    }
+
+    /// Returns the value of a mapping at a key, at the replayed height.
+    pub fn mapping_value(
+        &self,
+        program_id: &ProgramID,
+        mapping: &Identifier,
+        key: &Plaintext,
+    ) -> Result<Option<Value>> {
+        self.vm
+            .finalize_store()
+            .get_value_confirmed(*program_id, *mapping, key)
+    }
+
+    /// Returns the entries of a mapping, at the replayed height.
+    pub fn mapping_entries(
+        &self,
+        program_id: &ProgramID,
+        mapping: &Identifier,
+    ) -> Result<Vec<MappingEntry>> {
+        Ok(self
+            .vm
+            .finalize_store()
+            .get_mapping_confirmed(*program_id, *mapping)?
+            .into_iter()
+            .map(|(key, value)| MappingEntry { key, value })
+            .collect())
+    }
+
+    /// Returns the entries of every mapping of a program, at the replayed height.
+    pub fn snapshot(&self, program_id: &ProgramID) -> Result<MappingSnapshot> {
+        let height = self
+            .height()
+            .ok_or_else(|| anyhow!("No block has been replayed"))?;
+        let process = self.vm.process();
+        let process = process.read();
+        let program = process.get_program(program_id)?;
+        let mappings = program
+            .mappings()
+            .keys()
+            .map(|mapping| Ok((*mapping, self.mapping_entries(program_id, mapping)?)))
+            .collect::<Result<_>>()?;
+        Ok(MappingSnapshot {
+            program_id: *program_id,
+            height,
+            mappings,
+        })
+    }
+}
+
+/// An entry of a mapping.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingEntry {
+    pub key: Plaintext,
+    pub value: Value,
+}
+
+/// The entries of every mapping of a program at a block height.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingSnapshot {
+    pub program_id: ProgramID,
+    pub height: u32,
+    pub mappings: IndexMap<Identifier, Vec<MappingEntry>>,
+}
+
+impl MappingSnapshot {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Encodes the entries as CSV, with a `mapping,key,value` header.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("mapping,key,value\n");
+        for (mapping, entries) in &self.mappings {
+            for entry in entries {
+                csv.push_str(&format!(
+                    "{},{},{}\n",
+                    csv_field(&mapping.to_string()),
+                    csv_field(&entry.key.to_string()),
+                    csv_field(&entry.value.to_string())
+                ));
+            }
+        }
+        csv
+    }
+}
+
+// Quotes a CSV field if it contains a separator, a quote or a line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The values of mapping entries at past block heights.
+///
+/// The values of tracked keys are recorded at each replayed block, so that queries at any height up
+/// to the replayed height are answered from the cache. Keys should be tracked before the first
+/// query: tracking a key once the replay has started replays the blocks again from genesis.
+///
+/// # Example
+/// ```ignore
+/// use aleo_agent::agent::Agent;
+///
+/// let agent = Agent::default();
+/// let address = agent.address();
+/// let mut history = agent.mapping_history().unwrap();
+/// history.track_account(&address).unwrap();
+/// let before = history.account_balance_at(&address, 100_000).unwrap();
+/// let after = history.account_balance_at(&address, 120_000).unwrap();
+/// ```
+pub struct MappingHistory<'agent> {
+    replay: MappingReplay<'agent>,
+    keys: IndexMap<String, TrackedKey>,
+}
+
+// A tracked mapping key and the heights at which its value changed
+struct TrackedKey {
+    program_id: ProgramID,
+    mapping: Identifier,
+    key: Plaintext,
+    changes: BTreeMap<u32, Option<Value>>,
+}
+
+impl Agent {
+    /// Creates a mapping history, with no tracked key.
+    pub fn mapping_history(&self) -> Result<MappingHistory> {
+        Ok(MappingHistory {
+            replay: self.mapping_replay()?,
+            keys: IndexMap::new(),
+        })
+    }
+}
+
+impl<'agent> MappingHistory<'agent> {
+    /// Tracks the values of a mapping key.
+    pub fn track(
+        &mut self,
+        program_id: &ProgramID,
+        mapping: &Identifier,
+        key: &Plaintext,
+    ) -> Result<()> {
+        let name = format!("{program_id}/{mapping}[{key}]");
+        if self.keys.contains_key(&name) {
+            return Ok(());
+        }
+        if self.replay.height().is_some() {
+            // The past values of the key are unknown, replay from genesis
+            self.replay = self.replay.agent.mapping_replay()?;
+            self.keys
+                .values_mut()
+                .for_each(|tracked| tracked.changes.clear());
+        }
+        self.keys.insert(
+            name,
+            TrackedKey {
+                program_id: *program_id,
+                mapping: *mapping,
+                key: key.clone(),
+                changes: BTreeMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Tracks the public balance of an address, in the `account` mapping of `credits.aleo`.
+    pub fn track_account(&mut self, address: &Address) -> Result<()> {
+        self.track(
+            &ProgramID::from_str("credits.aleo")?,
+            &Identifier::from_str("account")?,
+            &Plaintext::from(Literal::Address(*address)),
+        )
+    }
+
+    /// Returns the value of a mapping key at a block height, tracking the key if needed.
+    ///
+    /// # Arguments
+    /// * `program_id` - The program of the mapping
+    /// * `mapping` - The name of the mapping
+    /// * `key` - The key of the entry
+    /// * `height` - The block height, the value being the one after the block
+    pub fn value_at(
+        &mut self,
+        program_id: &ProgramID,
+        mapping: &Identifier,
+        key: &Plaintext,
+        height: u32,
+    ) -> Result<Option<Value>> {
+        self.track(program_id, mapping, key)?;
+        self.replay_to(height)?;
+        let name = format!("{program_id}/{mapping}[{key}]");
+        Ok(value_at_height(&self.keys[&name].changes, height))
+    }
+
+    /// Returns the public balance of an address at a block height, in microcredits.
+    pub fn account_balance_at(&mut self, address: &Address, height: u32) -> Result<u64> {
+        let value = self.value_at(
+            &ProgramID::from_str("credits.aleo")?,
+            &Identifier::from_str("account")?,
+            &Plaintext::from(Literal::Address(*address)),
+            height,
+        )?;
+        match value {
+            Some(Value::Plaintext(Plaintext::Literal(Literal::U64(amount), _))) => Ok(*amount),
+            Some(value) => Err(anyhow!("Unexpected balance {value}")),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the height of the last replayed block, if any.
+    pub fn height(&self) -> Option<u32> {
+        self.replay.height()
+    }
+
+    // Replays the blocks up to `height`, recording the changes of the tracked keys
+    fn replay_to(&mut self, height: u32) -> Result<()> {
+        if self.replay.next_height > height {
+            // The values up to `height` are cached
+            return Ok(());
+        }
+        let keys = &mut self.keys;
+        self.replay.sync_to_with(height, |replay, block| {
+            for tracked in keys.values_mut() {
+                let value =
+                    replay.mapping_value(&tracked.program_id, &tracked.mapping, &tracked.key)?;
+                if value_at_height(&tracked.changes, block.height()) != value {
+                    tracked.changes.insert(block.height(), value);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+// Returns the value after the last change up to `height`
+fn value_at_height(changes: &BTreeMap<u32, Option<Value>>, height: u32) -> Option<Value> {
+    changes
+        .range(..=height)
+        .next_back()
+        .and_then(|(_, value)| value.clone())
+}
+
+impl<'agent> ProgramManager<'agent> {
+    /// Returns the current entries of a mapping of the program, fetched from a node serving whole
+    /// mappings at `/program/{program}/mapping/{mapping}`, as indexing nodes do.
+    ///
+    /// # Errors
+    /// If the node does not serve whole mappings: see
+    /// [mapping_entries_or_replay](ProgramManager::mapping_entries_or_replay) to replay the blocks
+    /// instead.
+    ///
+    /// # Arguments
+    /// * `mapping` - The name of the mapping
+    pub fn mapping_entries(&self, mapping: impl TryInto<Identifier>) -> Result<Vec<MappingEntry>> {
+        let mapping = mapping
+            .try_into()
+            .map_err(|_| anyhow!("Invalid mapping name"))?;
+        self.get_mapping_entries_from_node(&mapping)
+            .map_err(|error| match is_not_found(&error) {
+                true => error.context(
+                    "The node does not serve whole mappings, use `mapping_entries_or_replay`",
+                ),
+                false => error,
+            })
+    }
+
+    /// Returns the current entries of a mapping of the program, fetched from the node, or
+    /// reconstructed by replaying every block if the node does not serve whole mappings.
+    ///
+    /// The replay fetches every block from genesis, see [MappingReplay]: it only happens when the
+    /// node answers that it does not serve the mapping, not when it fails to answer.
+    ///
+    /// # Arguments
+    /// * `mapping` - The name of the mapping
+    pub fn mapping_entries_or_replay(
+        &self,
+        mapping: impl TryInto<Identifier>,
+    ) -> Result<Vec<MappingEntry>> {
+        let mapping = mapping
+            .try_into()
+            .map_err(|_| anyhow!("Invalid mapping name"))?;
+        match self.get_mapping_entries_from_node(&mapping) {
+            Ok(entries) => Ok(entries),
+            Err(error) if is_not_found(&error) => {
+                let mut replay = self.agent().mapping_replay()?;
+                replay.sync()?;
+                replay.mapping_entries(self.program_id(), &mapping)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Exports the entries of every mapping of the program at a block height, by replaying the
+    /// blocks up to it.
+    ///
+    /// # Arguments
+    /// * `height` - The block height of the snapshot
+    pub fn export_mappings(&self, height: u32) -> Result<MappingSnapshot> {
+        let mut replay = self.agent().mapping_replay()?;
+        replay.sync_to(height)?;
+        replay.snapshot(self.program_id())
+    }
+
+    // Fetches the entries of a mapping from a node serving whole mappings
+    fn get_mapping_entries_from_node(&self, mapping: &Identifier) -> Result<Vec<MappingEntry>> {
+        let path = format!("/program/{}/mapping/{mapping}", self.program_id());
+        let entries: Vec<(String, String)> = self.agent().get_json(&path)?;
+        entries
+            .into_iter()
+            .map(|(key, value)| {
+                Ok(MappingEntry {
+                    key: Plaintext::from_str(&key)?,
+                    value: Value::from_str(&value)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::backend::{MemoryLedger, NoBlocks};
+
+    #[test]
+    fn test_snapshot_export() {
+        let program_id = ProgramID::from_str("token.aleo").unwrap();
+        let balances = Identifier::from_str("balances").unwrap();
+        let owners = Identifier::from_str("owners").unwrap();
+        let snapshot = MappingSnapshot {
+            program_id,
+            height: 42,
+            mappings: IndexMap::from([
+                (
+                    balances,
+                    vec![MappingEntry {
+                        key: Plaintext::from_str("1field").unwrap(),
+                        value: Value::from_str("5u64").unwrap(),
+                    }],
+                ),
+                (
+                    owners,
+                    vec![MappingEntry {
+                        key: Plaintext::from_str("2u8").unwrap(),
+                        value: Value::from_str("{ a: 1u8, b: 2u8 }").unwrap(),
+                    }],
+                ),
+            ]),
+        };
+
+        let csv = snapshot.to_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "mapping,key,value");
+        assert_eq!(lines[1], "balances,1field,5u64");
+        assert!(lines[2].starts_with("owners,2u8,\"{"));
+
+        let json = snapshot.to_json().unwrap();
+        assert_eq!(
+            serde_json::from_str::<MappingSnapshot>(&json).unwrap(),
+            snapshot
+        );
+    }
+
+    #[test]
+    fn test_value_at_height() {
+        let five = Value::from_str("5u64").unwrap();
+        let changes = BTreeMap::from([(10, Some(five.clone())), (20, None)]);
+        assert_eq!(value_at_height(&changes, 9), None);
+        assert_eq!(value_at_height(&changes, 10), Some(five.clone()));
+        assert_eq!(value_at_height(&changes, 19), Some(five));
+        assert_eq!(value_at_height(&changes, 25), None);
+    }
+
+    #[test]
+    fn test_replay_memory_ledger() {
+        let ledger = Arc::new(MemoryLedger::new(0).unwrap());
+        let address = Address::zero();
+        ledger.fund(&address, MICROCREDITS).unwrap();
+        let agent = Agent::builder().with_backend(ledger).build();
+
+        // The ledger does not serve whole mappings, the replay is opt-in
+        let pm = agent.program("credits.aleo").unwrap();
+        assert!(pm.mapping_entries("account").is_err());
+        let entries = pm.mapping_entries_or_replay("account").unwrap();
+        let key = Plaintext::from(Literal::Address(address));
+        assert!(entries.iter().any(|entry| entry.key == key));
+
+        let mut heights = vec![];
+        let mut replay = agent.mapping_replay().unwrap();
+        replay
+            .sync_to_with(1, |replay, block| {
+                assert_eq!(replay.height(), Some(block.height()));
+                heights.push(block.height());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(heights, vec![0, 1]);
+
+        let mut history = agent.mapping_history().unwrap();
+        assert_eq!(
+            history.account_balance_at(&address, 1).unwrap(),
+            MICROCREDITS
+        );
+        assert_eq!(history.account_balance_at(&address, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_replay_without_blocks() {
+        let agent = Agent::builder().with_backend(Arc::new(NoBlocks)).build();
+        let mut replay = agent.mapping_replay().unwrap();
+        let error = replay.sync_to(3).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("The node returned no block at height 0"));
+        assert_eq!(replay.height(), None);
+    }
+}
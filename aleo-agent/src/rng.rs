@@ -0,0 +1,83 @@
+//! Random number generation for proving and signing
+//!
+//! Transactions and signatures are randomized: by default, the agent draws from the thread RNG.
+//! For reproducible tests and simulations, an agent can draw from a stream seeded with
+//! [AgentBuilder::with_rng_seed](crate::builder::AgentBuilder::with_rng_seed), and calls such as
+//! [Agent::transfer_with_rng] take an RNG of their own.
+//!
+//! # Example
+//! ```ignore
+//! use aleo_agent::agent::{Agent, TransferArgs, TransferType};
+//! use aleo_agent::MICROCREDITS;
+//!
+//! // Two runs of the same agent against the same ledger produce the same transactions
+//! let agent = Agent::builder().with_rng_seed(42).build();
+//! let args = TransferArgs::from(MICROCREDITS, recipient, 0, None, TransferType::Public);
+//! agent.transfer(args).unwrap();
+//! ```
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaChaRng;
+
+use crate::agent::Agent;
+
+/// A cryptographically secure RNG, usable as a trait object.
+pub trait CryptoRngCore: RngCore + CryptoRng {}
+
+impl<T: RngCore + CryptoRng + ?Sized> CryptoRngCore for T {}
+
+impl Agent {
+    /// Draws the randomness of the agent from a stream seeded with `seed`, which is shared by the
+    /// clones of the agent.
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        self.set_rng(Some(Arc::new(Mutex::new(ChaChaRng::seed_from_u64(seed)))));
+    }
+
+    /// Draws the randomness of the agent from the thread RNG again.
+    pub fn remove_rng_seed(&mut self) {
+        self.set_rng(None);
+    }
+
+    // Runs `f` with the seeded stream of the agent, or with the thread RNG. A stream left poisoned
+    // by a panic is still used: its state is valid, as drawing from it cannot be interrupted halfway
+    pub(crate) fn with_rng<T>(&self, f: impl FnOnce(&mut dyn CryptoRngCore) -> T) -> T {
+        match self.rng() {
+            Some(rng) => f(&mut *rng.lock().unwrap_or_else(PoisonError::into_inner)),
+            None => f(&mut rand::thread_rng()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::account::Account;
+
+    #[test]
+    fn test_seeded_rng() {
+        let account = Account::from_seed(1).unwrap();
+        let sign = |seed| account.sign_with_rng(b"hello", &mut ChaChaRng::seed_from_u64(seed));
+        assert_eq!(sign(7).unwrap(), sign(7).unwrap());
+        assert_ne!(sign(7).unwrap(), sign(8).unwrap());
+
+        let draw = |agent: &Agent| agent.with_rng(|rng| rng.next_u64());
+        let mut agent = Agent::builder().with_rng_seed(7).build();
+        let first = draw(&agent);
+        assert_ne!(draw(&agent), first);
+        agent.set_rng_seed(7);
+        assert_eq!(draw(&agent.clone()), first);
+        assert_ne!(draw(&agent), first);
+    }
+
+    #[test]
+    fn test_poisoned_rng() {
+        let agent = Agent::builder().with_rng_seed(7).build();
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            agent.with_rng(|_| panic!("panic while drawing"))
+        }));
+        assert!(panicked.is_err());
+        agent.with_rng(|rng| rng.next_u64());
+    }
+}
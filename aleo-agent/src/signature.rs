@@ -0,0 +1,333 @@
+//! Off-chain message signing and verification
+//!
+//! Messages are signed as bytes, field elements or program [Value]s, and verified against any
+//! address. Messages signed for an application are prefixed with its domain, so that a signature
+//! requested by one application cannot be replayed to another one, or mistaken for a transaction.
+//!
+//! # Example
+//! ```
+//! use aleo_agent::account::Account;
+//! use aleo_agent::signature::{verify, verify_with_domain};
+//!
+//! let account = Account::new().unwrap();
+//! let signature = account.sign_with_domain("example.com", b"hello").unwrap();
+//!
+//! assert!(verify_with_domain(account.address(), "example.com", b"hello", &signature));
+//! assert!(!verify_with_domain(account.address(), "evil.com", b"hello", &signature));
+//! assert!(!verify(account.address(), b"hello", &signature));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, ensure, Result};
+use snarkvm::prelude::{FromBytes, ToBytes, ToFields};
+
+use crate::account::Account;
+use crate::rng::CryptoRngCore;
+
+use super::*;
+
+/// The prefix of every domain-separated message.
+pub const MESSAGE_PREFIX: &str = "Aleo Signed Message:\n";
+
+/// Verifies a signature of bytes by an address.
+pub fn verify(address: &Address, message: &[u8], signature: &Signature) -> bool {
+    signature.verify_bytes(address, message)
+}
+
+/// Verifies a signature of field elements by an address.
+pub fn verify_fields(address: &Address, message: &[Field], signature: &Signature) -> bool {
+    signature.verify(address, message)
+}
+
+/// Verifies a signature of a program value by an address.
+pub fn verify_value(address: &Address, value: &Value, signature: &Signature) -> bool {
+    match value.to_fields() {
+        Ok(fields) => signature.verify(address, &fields),
+        Err(_) => false,
+    }
+}
+
+/// Verifies a signature of a message prefixed with the domain of an application.
+pub fn verify_with_domain(
+    address: &Address,
+    domain: &str,
+    message: &[u8],
+    signature: &Signature,
+) -> bool {
+    signature.verify_bytes(address, &domain_message(domain, message))
+}
+
+/// Returns the bytes signed for a message of an application: [MESSAGE_PREFIX], the length of the
+/// domain, the domain, a new line and the message.
+pub fn domain_message(domain: &str, message: &[u8]) -> Vec<u8> {
+    let mut bytes = MESSAGE_PREFIX.as_bytes().to_vec();
+    bytes.extend_from_slice(format!("{}:{domain}\n", domain.len()).as_bytes());
+    bytes.extend_from_slice(message);
+    bytes
+}
+
+/// Encodes a signature as hexadecimal bytes, an alternative to its `sign1...` string.
+pub fn signature_to_hex(signature: &Signature) -> Result<String> {
+    Ok(hex::encode(signature.to_bytes_le()?))
+}
+
+/// Parses a signature from its `sign1...` string or from hexadecimal bytes.
+pub fn parse_signature(signature: &str) -> Result<Signature> {
+    let signature = signature.trim();
+    if signature.starts_with("sign1") {
+        return Signature::from_str(signature);
+    }
+    let bytes = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|_| anyhow!("Invalid signature encoding"))?;
+    Signature::from_bytes_le(&bytes)
+}
+
+impl Account {
+    /// Signs field elements with the private key.
+    pub fn sign_fields(&self, message: &[Field]) -> Result<Signature> {
+        self.sign_fields_with_rng(message, &mut rand::thread_rng())
+    }
+
+    /// Signs field elements with the private key, drawing the signature nonce from `rng`.
+    pub fn sign_fields_with_rng(
+        &self,
+        message: &[Field],
+        mut rng: &mut dyn CryptoRngCore,
+    ) -> Result<Signature> {
+        self.expose_private_key()?.sign(message, &mut rng)
+    }
+
+    /// Signs a program value, such as a struct, with the private key.
+    ///
+    /// # Example
+    /// ```
+    /// use std::str::FromStr;
+    /// use aleo_agent::account::Account;
+    /// use aleo_agent::signature::verify_value;
+    /// use aleo_agent::Value;
+    ///
+    /// let account = Account::new().unwrap();
+    /// let value = Value::from_str("{ amount: 10u64, nonce: 1u32 }").unwrap();
+    /// let signature = account.sign_value(&value).unwrap();
+    ///
+    /// assert!(verify_value(account.address(), &value, &signature));
+    /// ```
+    pub fn sign_value(&self, value: &Value) -> Result<Signature> {
+        self.sign_fields(&value.to_fields()?)
+    }
+
+    /// Signs a program value, drawing the signature nonce from `rng`.
+    pub fn sign_value_with_rng(
+        &self,
+        value: &Value,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Signature> {
+        self.sign_fields_with_rng(&value.to_fields()?, rng)
+    }
+
+    /// Signs a message prefixed with the domain of an application, see [domain_message].
+    pub fn sign_with_domain(&self, domain: &str, message: &[u8]) -> Result<Signature> {
+        self.sign(&domain_message(domain, message))
+    }
+
+    /// Signs a message prefixed with the domain of an application, drawing the signature nonce
+    /// from `rng`.
+    pub fn sign_with_domain_with_rng(
+        &self,
+        domain: &str,
+        message: &[u8],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Signature> {
+        self.sign_with_rng(&domain_message(domain, message), rng)
+    }
+}
+
+/// A login challenge, signed by a wallet to prove the ownership of an address to an application.
+///
+/// The challenge is sent to the wallet as text, and signed with [Account::sign_with_domain]. The
+/// application keeps the challenge and checks the signature with [LoginChallenge::verify].
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use aleo_agent::account::Account;
+/// use aleo_agent::signature::LoginChallenge;
+///
+/// let account = Account::new().unwrap();
+/// let challenge = LoginChallenge::new("example.com", *account.address());
+///
+/// // On the wallet
+/// let signature = account
+///     .sign_with_domain(&challenge.domain, challenge.to_string().as_bytes())
+///     .unwrap();
+///
+/// // On the application
+/// challenge.verify(&signature, Duration::from_secs(300)).expect("Invalid login");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoginChallenge {
+    pub domain: String,
+    pub address: Address,
+    pub nonce: String,
+    /// The creation time of the challenge, in seconds since the Unix epoch.
+    pub issued_at: u64,
+}
+
+impl LoginChallenge {
+    /// Creates a challenge with a random nonce.
+    pub fn new<S: Into<String>>(domain: S, address: Address) -> Self {
+        Self::new_with_rng(domain, address, &mut rand::thread_rng())
+    }
+
+    /// Creates a challenge with a nonce drawn from `rng`.
+    pub fn new_with_rng<S: Into<String>>(
+        domain: S,
+        address: Address,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Self {
+        let mut nonce = [0u8; 16];
+        rng.fill_bytes(&mut nonce);
+        Self {
+            domain: domain.into(),
+            address,
+            nonce: hex::encode(nonce),
+            issued_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    /// Checks that the challenge was signed by its address and has not expired.
+    pub fn verify(&self, signature: &Signature, max_age: Duration) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        ensure!(
+            now.saturating_sub(self.issued_at) <= max_age.as_secs(),
+            "The login challenge has expired"
+        );
+        if !verify_with_domain(
+            &self.address,
+            &self.domain,
+            self.to_string().as_bytes(),
+            signature,
+        ) {
+            bail!(
+                "Invalid signature of the login challenge by {}",
+                self.address
+            );
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoginChallenge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} wants you to sign in with your Aleo account:\n{}\n\nNonce: {}\nIssued At: {}",
+            self.domain, self.address, self.nonce, self.issued_at
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let account = Account::new().unwrap();
+        let other = Account::new().unwrap();
+
+        let signature = account.sign(b"hello").unwrap();
+        assert!(verify(account.address(), b"hello", &signature));
+        assert!(!verify(other.address(), b"hello", &signature));
+
+        let fields = [Field::from_u64(1), Field::from_u64(2)];
+        let signature = account.sign_fields(&fields).unwrap();
+        assert!(verify_fields(account.address(), &fields, &signature));
+        assert!(!verify_fields(account.address(), &fields[..1], &signature));
+
+        let signature = account.sign_with_domain("example.com", b"hello").unwrap();
+        assert!(verify_with_domain(
+            account.address(),
+            "example.com",
+            b"hello",
+            &signature
+        ));
+        assert!(!verify_with_domain(
+            account.address(),
+            "example.co",
+            b"mhello",
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_sign_with_rng() {
+        let account = Account::from_seed(1).unwrap();
+        let rng = |seed| ChaChaRng::seed_from_u64(seed);
+        let value = Value::from_str("{ amount: 10u64 }").unwrap();
+        let sign_value = |seed| account.sign_value_with_rng(&value, &mut rng(seed)).unwrap();
+        assert_eq!(sign_value(7), sign_value(7));
+        assert_ne!(sign_value(7), sign_value(8));
+        assert!(verify_value(account.address(), &value, &sign_value(7)));
+
+        let sign_domain = |seed| {
+            account
+                .sign_with_domain_with_rng("example.com", b"hello", &mut rng(seed))
+                .unwrap()
+        };
+        assert_eq!(sign_domain(7), sign_domain(7));
+        assert!(verify_with_domain(
+            account.address(),
+            "example.com",
+            b"hello",
+            &sign_domain(7)
+        ));
+
+        let challenge =
+            |seed| LoginChallenge::new_with_rng("example.com", *account.address(), &mut rng(seed));
+        assert_eq!(challenge(7).nonce, challenge(7).nonce);
+        assert_ne!(challenge(7).nonce, challenge(8).nonce);
+    }
+
+    #[test]
+    fn test_signature_encodings() {
+        let account = Account::new().unwrap();
+        let signature = account.sign(b"hello").unwrap();
+        let hex = signature_to_hex(&signature).unwrap();
+        assert_eq!(parse_signature(&hex).unwrap(), signature);
+        assert_eq!(parse_signature(&format!("0x{hex}")).unwrap(), signature);
+        assert_eq!(parse_signature(&signature.to_string()).unwrap(), signature);
+        assert!(parse_signature("not a signature").is_err());
+    }
+
+    #[test]
+    fn test_login_challenge() {
+        let account = Account::new().unwrap();
+        let challenge = LoginChallenge::new("example.com", *account.address());
+        let signature = account
+            .sign_with_domain("example.com", challenge.to_string().as_bytes())
+            .unwrap();
+        assert!(challenge
+            .verify(&signature, Duration::from_secs(60))
+            .is_ok());
+
+        let mut other_nonce = challenge.clone();
+        other_nonce.nonce = "00".to_string();
+        assert!(other_nonce
+            .verify(&signature, Duration::from_secs(60))
+            .is_err());
+
+        let mut expired = challenge;
+        expired.issued_at -= 120;
+        assert!(expired.verify(&signature, Duration::from_secs(60)).is_err());
+    }
+}
@@ -0,0 +1,512 @@
+//! Signing of transactions by local or external key custody
+//!
+//! Every spend is built from [Authorization]s: the signed requests of the transitions to prove.
+//! A [Signer] produces them, so that the private key can live outside of the application process,
+//! in an HSM or a signing daemon. Proving happens locally, and only needs the authorizations.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Result};
+use snarkvm::ledger::block::ProgramOwner;
+use snarkvm::synthesizer::process::{deployment_cost, execution_cost};
+
+use crate::account::{Account, Secret};
+use crate::agent::Agent;
+use crate::rng::CryptoRngCore;
+
+use super::*;
+
+/// A custodian of a private key, producing signatures and authorizations.
+///
+/// Implementations for remote custody forward the requests to the key holder, and typically hold
+/// their own copy of the programs. The [Process] given to each method holds the programs of the
+/// request, for implementations that build the authorization themselves. The RNG is the one of the
+/// agent, see [crate::rng]: implementations for remote custody may use their own.
+pub trait Signer: Send + Sync {
+    /// Returns the address of the signing key.
+    fn address(&self) -> Address;
+
+    /// Returns the view key of the signing key, if the custodian discloses it.
+    ///
+    /// The agent needs it to find and decrypt the records of the account: without it, calls bound to
+    /// the records of the account fail.
+    fn view_key(&self) -> Option<&ViewKey> {
+        None
+    }
+
+    /// Signs a message made of field elements.
+    fn sign(&self, message: &[Field], rng: &mut dyn CryptoRngCore) -> Result<Signature>;
+
+    /// Authorizes a call to `function_name` of `program_id` with `inputs`.
+    fn authorize(
+        &self,
+        process: &Process,
+        program_id: &ProgramID,
+        function_name: &Identifier,
+        inputs: &[Value],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization>;
+
+    /// Authorizes a fee paid from the public balance for a deployment or an execution.
+    fn authorize_fee_public(
+        &self,
+        process: &Process,
+        base_fee: u64,
+        priority_fee: u64,
+        deployment_or_execution_id: Field,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization>;
+
+    /// Authorizes a fee paid with a record for a deployment or an execution.
+    fn authorize_fee_private(
+        &self,
+        process: &Process,
+        fee_record: PlaintextRecord,
+        base_fee: u64,
+        priority_fee: u64,
+        deployment_or_execution_id: Field,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization>;
+}
+
+/// A [Signer] holding its private key in memory.
+#[derive(Clone, Debug)]
+pub struct LocalSigner {
+    private_key: Secret<PrivateKey>,
+    view_key: Secret<ViewKey>,
+    address: Address,
+}
+
+impl LocalSigner {
+    pub fn new(private_key: PrivateKey) -> Result<Self> {
+        let view_key = ViewKey::try_from(&private_key)?;
+        let address = Address::try_from(&private_key)?;
+        Ok(Self {
+            private_key: Secret::new(private_key),
+            view_key: Secret::new(view_key),
+            address,
+        })
+    }
+
+    /// Creates a signer from the private key of an account, failing for watch-only accounts.
+    pub fn from_account(account: &Account) -> Result<Self> {
+        Ok(Self {
+            private_key: account.secret_private_key()?.clone(),
+            view_key: account.secret_view_key().clone(),
+            address: *account.address(),
+        })
+    }
+}
+
+impl Signer for LocalSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn view_key(&self) -> Option<&ViewKey> {
+        Some(self.view_key.expose())
+    }
+
+    fn sign(&self, message: &[Field], mut rng: &mut dyn CryptoRngCore) -> Result<Signature> {
+        self.private_key.expose().sign(message, &mut rng)
+    }
+
+    fn authorize(
+        &self,
+        process: &Process,
+        program_id: &ProgramID,
+        function_name: &Identifier,
+        inputs: &[Value],
+        mut rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization> {
+        process.authorize::<CurrentAleo, _>(
+            self.private_key.expose(),
+            program_id,
+            function_name,
+            inputs.iter(),
+            &mut rng,
+        )
+    }
+
+    fn authorize_fee_public(
+        &self,
+        process: &Process,
+        base_fee: u64,
+        priority_fee: u64,
+        deployment_or_execution_id: Field,
+        mut rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization> {
+        process.authorize_fee_public::<CurrentAleo, _>(
+            self.private_key.expose(),
+            base_fee,
+            priority_fee,
+            deployment_or_execution_id,
+            &mut rng,
+        )
+    }
+
+    fn authorize_fee_private(
+        &self,
+        process: &Process,
+        fee_record: PlaintextRecord,
+        base_fee: u64,
+        priority_fee: u64,
+        deployment_or_execution_id: Field,
+        mut rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization> {
+        process.authorize_fee_private::<CurrentAleo, _>(
+            self.private_key.expose(),
+            fee_record,
+            base_fee,
+            priority_fee,
+            deployment_or_execution_id,
+            &mut rng,
+        )
+    }
+}
+
+/// A [Signer] test double, recording every request it receives.
+///
+/// It signs with an in-memory key derived from a seed, or rejects every request. Like a remote
+/// custodian, it may keep its view key to itself.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use aleo_agent::agent::Agent;
+/// use aleo_agent::signer::{Signer, TestSigner};
+///
+/// let signer = Arc::new(TestSigner::from_seed(1).unwrap());
+/// let agent = Agent::builder().with_external_signer(signer.clone()).build();
+///
+/// assert_eq!(agent.signer().unwrap().address(), signer.address());
+/// ```
+#[derive(Debug)]
+pub struct TestSigner {
+    inner: LocalSigner,
+    reject: bool,
+    hide_view_key: bool,
+    requests: Mutex<Vec<String>>,
+}
+
+impl TestSigner {
+    /// Creates a signer with the key of [Account::from_seed].
+    pub fn from_seed(seed: u64) -> Result<Self> {
+        Ok(Self {
+            inner: LocalSigner::from_account(&Account::from_seed(seed)?)?,
+            reject: false,
+            hide_view_key: false,
+            requests: Mutex::new(vec![]),
+        })
+    }
+
+    /// Creates a signer rejecting every request, as a key holder denying approval would.
+    pub fn rejecting(seed: u64) -> Result<Self> {
+        Ok(Self {
+            reject: true,
+            ..Self::from_seed(seed)?
+        })
+    }
+
+    /// Creates a signer disclosing no view key.
+    pub fn without_view_key(seed: u64) -> Result<Self> {
+        Ok(Self {
+            hide_view_key: true,
+            ..Self::from_seed(seed)?
+        })
+    }
+
+    /// Returns the requests received so far, such as `authorize credits.aleo/transfer_public`.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().expect("poisoned lock").clone()
+    }
+
+    // Records a request, failing if the signer rejects requests
+    fn record(&self, request: String) -> Result<()> {
+        self.requests
+            .lock()
+            .expect("poisoned lock")
+            .push(request.clone());
+        if self.reject {
+            bail!("The signer rejected the request: {request}");
+        }
+        Ok(())
+    }
+}
+
+impl Signer for TestSigner {
+    fn address(&self) -> Address {
+        self.inner.address()
+    }
+
+    fn view_key(&self) -> Option<&ViewKey> {
+        match self.hide_view_key {
+            true => None,
+            false => self.inner.view_key(),
+        }
+    }
+
+    fn sign(&self, message: &[Field], rng: &mut dyn CryptoRngCore) -> Result<Signature> {
+        self.record(format!("sign {} fields", message.len()))?;
+        self.inner.sign(message, rng)
+    }
+
+    fn authorize(
+        &self,
+        process: &Process,
+        program_id: &ProgramID,
+        function_name: &Identifier,
+        inputs: &[Value],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization> {
+        self.record(format!("authorize {program_id}/{function_name}"))?;
+        self.inner
+            .authorize(process, program_id, function_name, inputs, rng)
+    }
+
+    fn authorize_fee_public(
+        &self,
+        process: &Process,
+        base_fee: u64,
+        priority_fee: u64,
+        deployment_or_execution_id: Field,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization> {
+        self.record(format!("authorize_fee_public {base_fee} {priority_fee}"))?;
+        self.inner.authorize_fee_public(
+            process,
+            base_fee,
+            priority_fee,
+            deployment_or_execution_id,
+            rng,
+        )
+    }
+
+    fn authorize_fee_private(
+        &self,
+        process: &Process,
+        fee_record: PlaintextRecord,
+        base_fee: u64,
+        priority_fee: u64,
+        deployment_or_execution_id: Field,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<Authorization> {
+        self.record(format!("authorize_fee_private {base_fee} {priority_fee}"))?;
+        self.inner.authorize_fee_private(
+            process,
+            fee_record,
+            base_fee,
+            priority_fee,
+            deployment_or_execution_id,
+            rng,
+        )
+    }
+}
+
+impl Agent {
+    /// Returns the signer of the agent: the external signer if one is set, or else a [LocalSigner]
+    /// holding the private key of the agent's account.
+    pub fn signer(&self) -> Result<Arc<dyn Signer>> {
+        match self.external_signer() {
+            Some(signer) => Ok(signer.clone()),
+            None => Ok(Arc::new(LocalSigner::from_account(self.account())?)),
+        }
+    }
+
+    /// Returns the address the agent acts for: the address of the external signer if one is set,
+    /// or else the address of the agent's account.
+    pub fn address(&self) -> Address {
+        match self.external_signer() {
+            Some(signer) => signer.address(),
+            None => *self.account().address(),
+        }
+    }
+
+    /// Returns the view key of the address the agent acts for, see [Agent::address].
+    ///
+    /// # Errors
+    /// If the external signer discloses no view key.
+    pub fn view_key(&self) -> Result<&ViewKey> {
+        match self.external_signer() {
+            Some(signer) => signer.view_key().ok_or_else(|| {
+                anyhow!(
+                    "The external signer of {} discloses no view key: the records of the address cannot be found",
+                    signer.address()
+                )
+            }),
+            None => Ok(self.account().view_key()),
+        }
+    }
+
+    // Returns the private key deriving the serial numbers of the records of the agent, failing with
+    // an external signer whose key is out of reach
+    pub(crate) fn serial_number_key(&self) -> Result<&PrivateKey> {
+        match self.external_signer() {
+            Some(signer) => bail!(
+                "The external signer of {} cannot derive serial numbers: pass them as a `SerialNumberSource`",
+                signer.address()
+            ),
+            None => self.account().expose_private_key(),
+        }
+    }
+
+    /// Creates an execution transaction signed by the agent's signer.
+    ///
+    /// The execution is proved locally, then the fee covering its cost is authorized and proved.
+    pub(crate) fn create_execute_transaction(
+        &self,
+        vm: &VM,
+        program_id: &ProgramID,
+        function_name: &Identifier,
+        inputs: &[Value],
+        priority_fee: u64,
+        fee_record: Option<PlaintextRecord>,
+        mut rng: &mut dyn CryptoRngCore,
+    ) -> Result<Transaction> {
+        let signer = self.signer()?;
+        let query = self.query()?;
+
+        let authorization = {
+            let process = vm.process();
+            let process = process.read();
+            signer.authorize(&process, program_id, function_name, inputs, rng)?
+        };
+        let execution =
+            match vm.execute_authorization(authorization, None, Some(query.clone()), &mut rng)? {
+                Transaction::Execute(_, execution, _) => execution,
+                _ => bail!("Expected an execution transaction"),
+            };
+
+        let fee_authorization = {
+            let process = vm.process();
+            let process = process.read();
+            let (base_fee, _) = execution_cost(&process, &execution)?;
+            let execution_id = execution.to_execution_id()?;
+            match fee_record {
+                Some(fee_record) => signer.authorize_fee_private(
+                    &process,
+                    fee_record,
+                    base_fee,
+                    priority_fee,
+                    execution_id,
+                    rng,
+                )?,
+                None => signer.authorize_fee_public(
+                    &process,
+                    base_fee,
+                    priority_fee,
+                    execution_id,
+                    rng,
+                )?,
+            }
+        };
+        let fee = vm.execute_fee_authorization(fee_authorization, Some(query), &mut rng)?;
+        Transaction::from_execution(execution, Some(fee))
+    }
+
+    /// Creates a deployment transaction signed by the agent's signer.
+    ///
+    /// The signer signs the deployment ID as the program owner, and authorizes the fee covering
+    /// the deployment cost.
+    pub(crate) fn create_deploy_transaction(
+        &self,
+        vm: &VM,
+        program: &Program,
+        priority_fee: u64,
+        fee_record: Option<PlaintextRecord>,
+        mut rng: &mut dyn CryptoRngCore,
+    ) -> Result<Transaction> {
+        let signer = self.signer()?;
+        let query = self.query()?;
+
+        let process = vm.process();
+        let deployment = process.read().deploy::<CurrentAleo, _>(program, &mut rng)?;
+        let deployment_id = deployment.to_deployment_id()?;
+        let owner = ProgramOwner::from(signer.address(), signer.sign(&[deployment_id], rng)?);
+
+        let (base_fee, _) = deployment_cost(&deployment)?;
+        let fee_authorization = {
+            let process = process.read();
+            match fee_record {
+                Some(fee_record) => signer.authorize_fee_private(
+                    &process,
+                    fee_record,
+                    base_fee,
+                    priority_fee,
+                    deployment_id,
+                    rng,
+                )?,
+                None => signer.authorize_fee_public(
+                    &process,
+                    base_fee,
+                    priority_fee,
+                    deployment_id,
+                    rng,
+                )?,
+            }
+        };
+        let fee = vm
+            .execute_fee_authorization(fee_authorization, Some(query), &mut rng)
+            .map_err(|error| anyhow!("Failed to prove the deployment fee: {error}"))?;
+        Transaction::from_deployment(owner, deployment, fee)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_test_signer() {
+        let signer = TestSigner::from_seed(7).unwrap();
+        assert_eq!(signer.address(), *Account::from_seed(7).unwrap().address());
+
+        let message = [Field::from_u64(1), Field::from_u64(2)];
+        let rng = &mut rand::thread_rng();
+        let signature = signer.sign(&message, rng).unwrap();
+        assert!(signature.verify(&signer.address(), &message));
+
+        let process = Process::load().unwrap();
+        let program_id = ProgramID::from_str("credits.aleo").unwrap();
+        let function_name = Identifier::from_str("transfer_public").unwrap();
+        let inputs = [
+            Value::from_str(&signer.address().to_string()).unwrap(),
+            Value::from_str("1u64").unwrap(),
+        ];
+        let authorization = signer
+            .authorize(&process, &program_id, &function_name, &inputs, rng)
+            .unwrap();
+        assert_eq!(authorization.len(), 1);
+        assert_eq!(
+            signer.requests(),
+            vec!["sign 2 fields", "authorize credits.aleo/transfer_public"]
+        );
+
+        let rejecting = TestSigner::rejecting(7).unwrap();
+        assert!(rejecting.sign(&message, rng).is_err());
+        assert_eq!(rejecting.requests(), vec!["sign 2 fields"]);
+    }
+
+    #[test]
+    fn test_external_signer_identity() {
+        let account = Account::from_seed(1).unwrap();
+        let external = Account::from_seed(2).unwrap();
+        let agent = Agent::builder()
+            .with_account(account.clone())
+            .with_external_signer(Arc::new(TestSigner::from_seed(2).unwrap()))
+            .build();
+        assert_eq!(agent.address(), *external.address());
+        assert_eq!(agent.view_key().unwrap(), external.view_key());
+        // The serial numbers of the external account cannot be derived from the default account
+        assert!(agent.serial_number_key().is_err());
+
+        let agent = Agent::builder()
+            .with_account(account)
+            .with_external_signer(Arc::new(TestSigner::without_view_key(2).unwrap()))
+            .build();
+        assert_eq!(agent.address(), *external.address());
+        assert!(agent.view_key().is_err());
+    }
+}
@@ -0,0 +1,366 @@
+//! Human-readable summaries of transactions
+//!
+//! A [TransactionSummary] lists what a transaction does without decrypting anything: its
+//! transitions with their public inputs and outputs, the records they create and consume, and its
+//! fee. It renders as text with [Display](fmt::Display), and as JSON with
+//! [TransactionSummary::to_json].
+//!
+//! # Example
+//! ```ignore
+//! use aleo_agent::agent::Agent;
+//!
+//! let agent = Agent::default();
+//! let summary = agent.get_transaction_summary("at1...").unwrap();
+//! println!("{summary}");
+//! ```
+
+use std::fmt;
+
+use anyhow::Result;
+use serde::Serialize;
+use snarkvm::console::program::Argument;
+use snarkvm::ledger::block::{Input, Output};
+
+use crate::agent::Agent;
+use crate::balance::credits;
+
+use super::*;
+
+/// The type of a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionKind {
+    /// A program deployment.
+    Deploy,
+    /// A program execution.
+    Execute,
+    /// A fee alone, paid for a rejected deployment or execution.
+    Fee,
+}
+
+impl fmt::Display for TransactionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionKind::Deploy => write!(f, "deploy"),
+            TransactionKind::Execute => write!(f, "execute"),
+            TransactionKind::Fee => write!(f, "fee"),
+        }
+    }
+}
+
+/// The fee of a transaction, in microcredits.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FeeSummary {
+    pub base: u64,
+    pub priority: u64,
+    /// The address paying the fee, unknown when the fee is paid with a private record.
+    pub payer: Option<Address>,
+}
+
+impl FeeSummary {
+    /// Returns the sum of the base and priority fees.
+    pub fn total(&self) -> u64 {
+        self.base.saturating_add(self.priority)
+    }
+}
+
+/// A transition of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TransitionSummary {
+    pub id: TransitionID,
+    pub program_id: ProgramID,
+    pub function_name: Identifier,
+    /// The constant and public inputs.
+    pub inputs: Vec<Value>,
+    /// The constant and public outputs, and the futures evaluated during finalize.
+    pub outputs: Vec<Value>,
+    /// The commitments of the records created.
+    pub records_created: Vec<Field>,
+    /// The serial numbers of the records consumed.
+    pub records_consumed: Vec<Field>,
+}
+
+impl TransitionSummary {
+    /// Summarizes a transition.
+    pub fn from_transition(transition: &Transition) -> Self {
+        let inputs = transition
+            .inputs()
+            .iter()
+            .filter_map(|input| match input {
+                Input::Constant(_, Some(plaintext)) | Input::Public(_, Some(plaintext)) => {
+                    Some(Value::Plaintext(plaintext.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        let outputs = transition
+            .outputs()
+            .iter()
+            .filter_map(|output| match output {
+                Output::Constant(_, Some(plaintext)) | Output::Public(_, Some(plaintext)) => {
+                    Some(Value::Plaintext(plaintext.clone()))
+                }
+                Output::Future(_, Some(future)) => Some(Value::Future(future.clone())),
+                _ => None,
+            })
+            .collect();
+        Self {
+            id: *transition.id(),
+            program_id: *transition.program_id(),
+            function_name: *transition.function_name(),
+            inputs,
+            outputs,
+            records_created: transition.commitments().copied().collect(),
+            records_consumed: transition.serial_numbers().copied().collect(),
+        }
+    }
+}
+
+/// A summary of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TransactionSummary {
+    pub id: TransactionID,
+    pub kind: TransactionKind,
+    /// The program deployed by a deployment.
+    pub deployed_program: Option<ProgramID>,
+    /// The transitions, including the fee transition.
+    pub transitions: Vec<TransitionSummary>,
+    pub fee: Option<FeeSummary>,
+}
+
+impl TransactionSummary {
+    /// Summarizes a transaction.
+    pub fn from_transaction(transaction: &Transaction) -> Result<Self> {
+        let (kind, deployed_program) = match transaction {
+            Transaction::Deploy(_, _, deployment, _) => {
+                (TransactionKind::Deploy, Some(*deployment.program_id()))
+            }
+            Transaction::Execute(..) => (TransactionKind::Execute, None),
+            Transaction::Fee(..) => (TransactionKind::Fee, None),
+        };
+        let fee = match transaction.fee_transition() {
+            Some(fee) => Some(FeeSummary {
+                base: *fee.base_amount()?,
+                priority: *fee.priority_amount()?,
+                payer: fee_payer(fee.transition()),
+            }),
+            None => None,
+        };
+        Ok(Self {
+            id: transaction.id(),
+            kind,
+            deployed_program,
+            transitions: transaction
+                .transitions()
+                .map(TransitionSummary::from_transition)
+                .collect(),
+            fee,
+        })
+    }
+
+    /// Returns the commitments of the records created by the transaction.
+    pub fn records_created(&self) -> impl Iterator<Item = &Field> {
+        self.transitions
+            .iter()
+            .flat_map(|transition| &transition.records_created)
+    }
+
+    /// Returns the serial numbers of the records consumed by the transaction.
+    pub fn records_consumed(&self) -> impl Iterator<Item = &Field> {
+        self.transitions
+            .iter()
+            .flat_map(|transition| &transition.records_consumed)
+    }
+
+    /// Encodes the summary as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl fmt::Display for TransactionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Transaction {}", self.id)?;
+        write!(f, "Type: {}", self.kind)?;
+        if let Some(program_id) = self.deployed_program {
+            write!(f, " of {program_id}")?;
+        }
+        if let Some(fee) = &self.fee {
+            write!(
+                f,
+                "\nFee:  {} (base {}, priority {}), ",
+                credits(fee.total()),
+                credits(fee.base),
+                credits(fee.priority)
+            )?;
+            match fee.payer {
+                Some(payer) => write!(f, "paid by {payer}")?,
+                None => write!(f, "paid with a private record")?,
+            }
+        }
+        for transition in &self.transitions {
+            write!(
+                f,
+                "\n\nTransition {}: {}/{}",
+                transition.id, transition.program_id, transition.function_name
+            )?;
+            for input in &transition.inputs {
+                write!(f, "\n  Input:   {}", one_line(input))?;
+            }
+            for output in &transition.outputs {
+                write!(f, "\n  Output:  {}", one_line(output))?;
+            }
+            for commitment in &transition.records_created {
+                write!(f, "\n  Created: record {commitment}")?;
+            }
+            for serial_number in &transition.records_consumed {
+                write!(f, "\n  Spent:   record {serial_number}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Agent {
+    /// Fetches a transaction from the network and summarizes it, see [TransactionSummary].
+    ///
+    /// # Arguments
+    /// * `transaction_id` - The id of the transaction to summarize
+    pub fn get_transaction_summary(&self, transaction_id: &str) -> Result<TransactionSummary> {
+        TransactionSummary::from_transaction(&self.get_transaction(transaction_id)?)
+    }
+}
+
+// Reads the payer of a public fee, the first argument of the `fee_public` future
+fn fee_payer(transition: &Transition) -> Option<Address> {
+    let future = transition
+        .outputs()
+        .iter()
+        .find_map(|output| match output {
+            Output::Future(_, Some(future)) => Some(future),
+            _ => None,
+        })?;
+    match future.arguments().first()? {
+        Argument::Plaintext(Plaintext::Literal(Literal::Address(address), _)) => Some(*address),
+        _ => None,
+    }
+}
+
+// Prints a struct or future value on a single line
+fn one_line(value: &Value) -> String {
+    value
+        .to_string()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::account::Account;
+    use crate::agent::{TransferArgs, TransferType};
+    use crate::backend::MemoryLedger;
+
+    #[test]
+    fn test_summary_rendering() {
+        let summary = TransactionSummary {
+            id: TransactionID::default(),
+            kind: TransactionKind::Execute,
+            deployed_program: None,
+            transitions: vec![TransitionSummary {
+                id: TransitionID::default(),
+                program_id: ProgramID::from_str("token.aleo").unwrap(),
+                function_name: Identifier::from_str("mint").unwrap(),
+                inputs: vec![Value::from_str("{ amount: 10u64, nonce: 1u32 }").unwrap()],
+                outputs: vec![],
+                records_created: vec![Field::from_str("7field").unwrap()],
+                records_consumed: vec![],
+            }],
+            fee: Some(FeeSummary {
+                base: 1_500,
+                priority: 500,
+                payer: None,
+            }),
+        };
+        assert_eq!(summary.records_created().count(), 1);
+        assert_eq!(summary.records_consumed().count(), 0);
+
+        let text = summary.to_string();
+        assert!(text.contains("Type: execute\n"));
+        assert!(text.contains("Fee:  0.002000 credits (base 0.001500 credits, priority 0.000500 credits), paid with a private record"));
+        assert!(text.contains(": token.aleo/mint\n  Input:   { amount: 10u64, nonce: 1u32 }\n"));
+        assert!(text.ends_with("  Created: record 7field"));
+
+        let json: serde_json::Value = serde_json::from_str(&summary.to_json().unwrap()).unwrap();
+        assert_eq!(json["kind"], "execute");
+        assert_eq!(json["fee"]["priority"], 500);
+        assert_eq!(json["transitions"][0]["function_name"], "mint");
+    }
+
+    #[test]
+    fn test_fee_total() {
+        let fee = FeeSummary {
+            base: u64::MAX,
+            priority: 1,
+            payer: None,
+        };
+        assert_eq!(fee.total(), u64::MAX);
+    }
+
+    #[test]
+    fn test_from_transaction() {
+        let ledger = Arc::new(MemoryLedger::new(0).unwrap());
+        let alice = Account::from_seed(1).unwrap();
+        let bob = Account::from_seed(2).unwrap();
+        ledger.fund(alice.address(), 10 * MICROCREDITS).unwrap();
+        let agent = Agent::builder()
+            .with_account(alice.clone())
+            .with_backend(ledger)
+            .build();
+
+        let args = TransferArgs::from(
+            MICROCREDITS,
+            *bob.address(),
+            0,
+            None,
+            TransferType::PublicToPrivate,
+        );
+        let transaction_id: String = serde_json::from_str(&agent.transfer(args).unwrap()).unwrap();
+        let summary = agent.get_transaction_summary(&transaction_id).unwrap();
+
+        assert_eq!(summary.kind, TransactionKind::Execute);
+        assert_eq!(summary.deployed_program, None);
+        let fee = summary.fee.as_ref().unwrap();
+        assert!(fee.base > 0);
+        assert_eq!(fee.priority, 0);
+        assert_eq!(fee.payer, Some(*alice.address()));
+
+        // The transfer, then the fee
+        let names = summary
+            .transitions
+            .iter()
+            .map(|transition| format!("{}/{}", transition.program_id, transition.function_name))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "credits.aleo/transfer_public_to_private",
+                "credits.aleo/fee_public"
+            ]
+        );
+        // The recipient is private, the amount is public
+        let transfer = &summary.transitions[0];
+        assert_eq!(
+            transfer.inputs,
+            vec![Value::from_str(&format!("{MICROCREDITS}u64")).unwrap()]
+        );
+        assert!(matches!(transfer.outputs.as_slice(), [Value::Future(_)]));
+        assert_eq!(transfer.records_created.len(), 1);
+        assert!(transfer.records_consumed.is_empty());
+        assert_eq!(summary.records_created().count(), 1);
+    }
+}
@@ -0,0 +1,358 @@
+//! Validation of function inputs against the signature declared in a program
+//!
+//! Inputs are checked before any proving key is synthesized, so that a wrong input count, type,
+//! visibility or record owner is reported immediately with the position of the offending argument.
+
+use snarkvm::console::program::{EntryType, PlaintextType, RecordType, ValueType};
+use thiserror::Error;
+
+use super::*;
+
+/// An error found while validating the inputs of a function call.
+#[derive(Debug, Error)]
+pub enum InputError {
+    #[error("Function '{function}' is not defined in program '{program}'")]
+    UnknownFunction {
+        program: ProgramID,
+        function: Identifier,
+    },
+    #[error("Function '{function}' expects {expected} inputs, found {found}")]
+    Count {
+        function: Identifier,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Input #{index} could not be parsed as a value")]
+    Parse { index: usize },
+    #[error("Input #{index}: expected {expected}, found '{found}'")]
+    Type {
+        index: usize,
+        expected: String,
+        found: String,
+    },
+    #[error("Input #{index}: expected {expected}, found {found}")]
+    Visibility {
+        index: usize,
+        expected: String,
+        found: String,
+    },
+    #[error("Input #{index}: record is owned by {owner}, not by the signer {signer}")]
+    RecordOwner {
+        index: usize,
+        owner: Address,
+        signer: Address,
+    },
+}
+
+/// Checks the inputs of `function` against its declared signature in `program`.
+///
+/// # Arguments
+/// * `program` - The program declaring the function
+/// * `function` - The name of the function to call
+/// * `inputs` - The inputs of the call
+/// * `signer` - The address that will sign the call, which must own every record input
+///
+/// # Returns
+/// The first [InputError] found, in input order.
+pub fn validate_inputs(
+    program: &Program,
+    function: &Identifier,
+    inputs: &[Value],
+    signer: &Address,
+) -> Result<(), InputError> {
+    let function_ref =
+        program
+            .get_function_ref(function)
+            .map_err(|_| InputError::UnknownFunction {
+                program: *program.id(),
+                function: *function,
+            })?;
+
+    let input_types = function_ref.input_types();
+    if input_types.len() != inputs.len() {
+        return Err(InputError::Count {
+            function: *function,
+            expected: input_types.len(),
+            found: inputs.len(),
+        });
+    }
+
+    input_types
+        .iter()
+        .zip(inputs)
+        .enumerate()
+        .try_for_each(|(index, (value_type, input))| {
+            validate_input(program, index, value_type, input, signer)
+        })
+}
+
+// Checks a single input against its declared value type
+fn validate_input(
+    program: &Program,
+    index: usize,
+    value_type: &ValueType<CurrentNetwork>,
+    input: &Value,
+    signer: &Address,
+) -> Result<(), InputError> {
+    match (value_type, input) {
+        (
+            ValueType::Constant(plaintext_type)
+            | ValueType::Public(plaintext_type)
+            | ValueType::Private(plaintext_type),
+            Value::Plaintext(plaintext),
+        ) => validate_plaintext(program, index, plaintext_type, plaintext),
+        (ValueType::Record(record_name), Value::Record(record)) => {
+            validate_record_owner(index, record, signer)?;
+            match program.get_record(record_name) {
+                Ok(record_type) => validate_record(program, index, &record_type, record),
+                Err(_) => Err(InputError::Type {
+                    index,
+                    expected: format!("a '{record_name}' record"),
+                    found: record.to_string(),
+                }),
+            }
+        }
+        (ValueType::ExternalRecord(_), Value::Record(record)) => {
+            validate_record_owner(index, record, signer)
+        }
+        (expected, found) => Err(InputError::Visibility {
+            index,
+            expected: format!("a {}", describe_value_type(expected)),
+            found: format!("a {}", describe_value(found)),
+        }),
+    }
+}
+
+// Checks that a record input is owned by the signer
+fn validate_record_owner(
+    index: usize,
+    record: &PlaintextRecord,
+    signer: &Address,
+) -> Result<(), InputError> {
+    let owner = **record.owner();
+    if &owner != signer {
+        return Err(InputError::RecordOwner {
+            index,
+            owner,
+            signer: *signer,
+        });
+    }
+    Ok(())
+}
+
+// Checks the owner visibility and entries of a record against its declared record type
+fn validate_record(
+    program: &Program,
+    index: usize,
+    record_type: &RecordType<CurrentNetwork>,
+    record: &PlaintextRecord,
+) -> Result<(), InputError> {
+    if record_type.owner().is_public() != record.owner().is_public() {
+        return Err(InputError::Visibility {
+            index,
+            expected: format!(
+                "a {} record owner",
+                visibility(record_type.owner().is_public())
+            ),
+            found: format!("a {} record owner", visibility(record.owner().is_public())),
+        });
+    }
+
+    if record.data().len() != record_type.entries().len() {
+        return Err(InputError::Type {
+            index,
+            expected: format!(
+                "a '{}' record with {} entries",
+                record_type.name(),
+                record_type.entries().len()
+            ),
+            found: record.to_string(),
+        });
+    }
+
+    for (name, entry_type) in record_type.entries() {
+        let entry = record.data().get(name).ok_or_else(|| InputError::Type {
+            index,
+            expected: format!("a '{}' record with entry '{name}'", record_type.name()),
+            found: record.to_string(),
+        })?;
+        let (plaintext_type, plaintext) = match (entry_type, entry) {
+            (EntryType::Constant(plaintext_type), Entry::Constant(plaintext))
+            | (EntryType::Public(plaintext_type), Entry::Public(plaintext))
+            | (EntryType::Private(plaintext_type), Entry::Private(plaintext)) => {
+                (plaintext_type, plaintext)
+            }
+            (entry_type, entry) => {
+                return Err(InputError::Visibility {
+                    index,
+                    expected: format!("entry '{name}' to be {}", entry_mode(entry_type)),
+                    found: format!("'{entry}'"),
+                })
+            }
+        };
+        validate_plaintext(program, index, plaintext_type, plaintext)?;
+    }
+    Ok(())
+}
+
+// Checks a plaintext value against its declared plaintext type, recursing into structs and arrays
+fn validate_plaintext(
+    program: &Program,
+    index: usize,
+    plaintext_type: &PlaintextType<CurrentNetwork>,
+    plaintext: &Plaintext,
+) -> Result<(), InputError> {
+    let mismatch = || InputError::Type {
+        index,
+        expected: plaintext_type.to_string(),
+        found: plaintext.to_string(),
+    };
+    match (plaintext_type, plaintext) {
+        (PlaintextType::Literal(literal_type), Plaintext::Literal(literal, _)) => {
+            if &literal.to_type() != literal_type {
+                return Err(mismatch());
+            }
+            Ok(())
+        }
+        (PlaintextType::Struct(struct_name), Plaintext::Struct(members, _)) => {
+            let struct_type = program.get_struct(struct_name).map_err(|_| mismatch())?;
+            if struct_type.members().len() != members.len() {
+                return Err(mismatch());
+            }
+            struct_type
+                .members()
+                .iter()
+                .try_for_each(|(name, member_type)| match members.get(name) {
+                    Some(member) => validate_plaintext(program, index, member_type, member),
+                    None => Err(mismatch()),
+                })
+        }
+        (PlaintextType::Array(array_type), Plaintext::Array(elements, _)) => {
+            if **array_type.length() as usize != elements.len() {
+                return Err(mismatch());
+            }
+            elements.iter().try_for_each(|element| {
+                validate_plaintext(program, index, array_type.next_element_type(), element)
+            })
+        }
+        _ => Err(mismatch()),
+    }
+}
+
+fn describe_value_type(value_type: &ValueType<CurrentNetwork>) -> String {
+    match value_type {
+        ValueType::Constant(plaintext_type) => format!("constant {plaintext_type}"),
+        ValueType::Public(plaintext_type) => format!("public {plaintext_type}"),
+        ValueType::Private(plaintext_type) => format!("private {plaintext_type}"),
+        ValueType::Record(name) => format!("'{name}' record"),
+        ValueType::ExternalRecord(locator) => format!("'{locator}' record"),
+        ValueType::Future(locator) => format!("'{locator}' future"),
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::Plaintext(plaintext) => format!("plaintext '{plaintext}'"),
+        Value::Record(_) => "record".to_string(),
+        Value::Future(future) => format!(
+            "'{}/{}' future",
+            future.program_id(),
+            future.function_name()
+        ),
+    }
+}
+
+fn entry_mode(entry_type: &EntryType<CurrentNetwork>) -> &'static str {
+    match entry_type {
+        EntryType::Constant(_) => "constant",
+        EntryType::Public(_) => "public",
+        EntryType::Private(_) => "private",
+    }
+}
+
+fn visibility(is_public: bool) -> &'static str {
+    match is_public {
+        true => "public",
+        false => "private",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::account::Account;
+
+    const PROGRAM: &str = r"
+program validation_test.aleo;
+
+struct point:
+    x as u32;
+    y as u32;
+
+record token:
+    owner as address.private;
+    amount as u64.private;
+
+function main:
+    input r0 as u32.public;
+    input r1 as point.private;
+    input r2 as token.record;
+    output r0 as u32.private;
+";
+
+    fn inputs(owner: &Address) -> Vec<Value> {
+        vec![
+            Value::from_str("1u32").unwrap(),
+            Value::from_str("{ x: 1u32, y: 2u32 }").unwrap(),
+            Value::from_str(&format!(
+                "{{ owner: {owner}.private, amount: 5u64.private, _nonce: 0group.public }}"
+            ))
+            .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_validate_inputs() {
+        let program = Program::from_str(PROGRAM).unwrap();
+        let function = Identifier::from_str("main").unwrap();
+        let signer = *Account::new().unwrap().address();
+
+        let valid = inputs(&signer);
+        assert!(validate_inputs(&program, &function, &valid, &signer).is_ok());
+
+        let res = validate_inputs(&program, &function, &valid[..2], &signer);
+        assert!(matches!(
+            res,
+            Err(InputError::Count {
+                expected: 3,
+                found: 2,
+                ..
+            })
+        ));
+
+        let mut wrong_type = valid.clone();
+        wrong_type[0] = Value::from_str("1u64").unwrap();
+        let res = validate_inputs(&program, &function, &wrong_type, &signer);
+        assert!(matches!(res, Err(InputError::Type { index: 0, .. })));
+
+        let mut wrong_member = valid.clone();
+        wrong_member[1] = Value::from_str("{ x: 1u32, z: 2u32 }").unwrap();
+        let res = validate_inputs(&program, &function, &wrong_member, &signer);
+        assert!(matches!(res, Err(InputError::Type { index: 1, .. })));
+
+        let mut plaintext_for_record = valid.clone();
+        plaintext_for_record[2] = Value::from_str("5u64").unwrap();
+        let res = validate_inputs(&program, &function, &plaintext_for_record, &signer);
+        assert!(matches!(res, Err(InputError::Visibility { index: 2, .. })));
+
+        let other = *Account::new().unwrap().address();
+        let res = validate_inputs(&program, &function, &inputs(&other), &signer);
+        assert!(matches!(res, Err(InputError::RecordOwner { index: 2, .. })));
+
+        let unknown = Identifier::from_str("missing").unwrap();
+        let res = validate_inputs(&program, &unknown, &valid, &signer);
+        assert!(matches!(res, Err(InputError::UnknownFunction { .. })));
+    }
+}
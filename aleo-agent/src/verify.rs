@@ -0,0 +1,157 @@
+//! Local verification of transactions
+//!
+//! Transactions are verified with an ephemeral VM holding the programs they use, so that an invalid
+//! deployment or execution is reported before it reaches the network.
+
+use anyhow::{bail, ensure, Result};
+use snarkvm::prelude::ToBytes;
+use snarkvm::synthesizer::process::{deployment_cost, execution_cost};
+
+use crate::agent::Agent;
+use crate::program::ProgramManager;
+
+use super::*;
+
+/// The maximum size of a transaction in bytes accepted by the network.
+pub const MAX_TRANSACTION_SIZE: usize = CurrentNetwork::MAX_TRANSACTION_SIZE;
+
+impl Agent {
+    /// Verifies a transaction locally.
+    ///
+    /// For a deployment, the program owner, the certificates of every function, the number of
+    /// constraints of every function and the fee are verified. For an execution, the proofs of the
+    /// transitions and the fee are verified. The programs used by the transaction, apart from a program
+    /// being deployed, are fetched from the network.
+    ///
+    /// # Arguments
+    /// * `transaction` - The transaction to verify
+    ///
+    /// # Example
+    /// ```ignore
+    /// use aleo_agent::agent::Agent;
+    ///
+    /// let agent = Agent::default();
+    /// let transaction = agent.get_transaction("at1...").expect("Failed to get transaction");
+    /// agent.verify_transaction(&transaction).expect("Invalid transaction");
+    /// ```
+    pub fn verify_transaction(&self, transaction: &Transaction) -> Result<()> {
+        let size = transaction.to_bytes_le()?.len();
+        ensure!(
+            size <= MAX_TRANSACTION_SIZE,
+            "Transaction {} is {size} bytes, the maximum is {MAX_TRANSACTION_SIZE} bytes",
+            transaction.id()
+        );
+
+        let rng = &mut rand::thread_rng();
+        match transaction {
+            Transaction::Deploy(_, owner, deployment, fee) => {
+                let program_id = deployment.program_id();
+                let deployment_id = deployment.to_deployment_id()?;
+                ensure!(
+                    owner.verify(deployment_id),
+                    "Invalid owner signature for the deployment of {program_id}"
+                );
+
+                for (function_name, (verifying_key, _)) in deployment.verifying_keys() {
+                    let num_constraints = verifying_key.circuit_info.num_constraints as u64;
+                    ensure!(
+                        num_constraints <= CurrentNetwork::MAX_DEPLOYMENT_LIMIT,
+                        "Function '{function_name}' of {program_id} has {num_constraints} constraints, the maximum is {}",
+                        CurrentNetwork::MAX_DEPLOYMENT_LIMIT
+                    );
+                }
+
+                let (cost, _) = deployment_cost(deployment)?;
+                let base_fee = *fee.base_amount()?;
+                ensure!(
+                    base_fee >= cost,
+                    "The deployment of {program_id} costs {cost} microcredits, but the base fee is {base_fee} microcredits"
+                );
+
+                // The imports are loaded, the program itself is added by the verification
+                let vm = self.initialize_vm(deployment.program())?;
+                let process = vm.process();
+                let process = process.read();
+                process.verify_deployment::<CurrentAleo, _>(deployment, rng)?;
+                process.verify_fee(fee, deployment_id)?;
+            }
+            Transaction::Execute(_, execution, fee) => {
+                let Some(transition) = execution.transitions().last() else {
+                    bail!("Execution {} has no transitions", transaction.id());
+                };
+                let program = self.get_program(transition.program_id())?;
+                let vm = ProgramManager::new(self, *program.id()).initialize_vm(&program)?;
+                let process = vm.process();
+                let process = process.read();
+                process.verify_execution(execution)?;
+
+                if let Some(fee) = fee {
+                    let (cost, _) = execution_cost(&process, execution)?;
+                    let base_fee = *fee.base_amount()?;
+                    ensure!(
+                        base_fee >= cost,
+                        "The execution costs {cost} microcredits, but the base fee is {base_fee} microcredits"
+                    );
+                    process.verify_fee(fee, execution.to_execution_id()?)?;
+                }
+            }
+            Transaction::Fee(_, fee) => {
+                let store = ConsensusStore::open(None)?;
+                let vm = VM::from(store)?;
+                let process = vm.process();
+                let process = process.read();
+                process.verify_fee(fee, fee.deployment_or_execution_id()?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::account::Account;
+    use crate::backend::MemoryLedger;
+
+    #[test]
+    fn test_verify_execution() {
+        let ledger = Arc::new(MemoryLedger::new(0).unwrap());
+        let alice = Account::from_seed(1).unwrap();
+        ledger.fund(alice.address(), 10 * MICROCREDITS).unwrap();
+        let agent = Agent::builder()
+            .with_account(alice)
+            .with_backend(ledger)
+            .build();
+
+        let credits = ProgramID::from_str("credits.aleo").unwrap();
+        let transfer = Identifier::from_str("transfer_public").unwrap();
+        let transaction = |amount: u64| {
+            let inputs = [
+                Value::from_str(&agent.account().address().to_string()).unwrap(),
+                Value::from_str(&format!("{amount}u64")).unwrap(),
+            ];
+            let vm = VM::from(ConsensusStore::open(None).unwrap()).unwrap();
+            agent
+                .with_rng(|rng| {
+                    agent
+                        .create_execute_transaction(&vm, &credits, &transfer, &inputs, 0, None, rng)
+                })
+                .unwrap()
+        };
+        let first = transaction(1);
+        let second = transaction(2);
+        assert!(agent.verify_transaction(&first).is_ok());
+
+        // The fee of the second transfer does not pay for the execution of the first one
+        let (Transaction::Execute(_, execution, _), Transaction::Execute(_, _, fee)) =
+            (&first, &second)
+        else {
+            panic!("Expected executions");
+        };
+        let tampered = Transaction::from_execution(execution.clone(), fee.clone()).unwrap();
+        assert!(agent.verify_transaction(&tampered).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Named accounts and the selection of the signer of an [Agent]
+
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, ensure, Result};
+use indexmap::IndexMap;
+
+use crate::account::Account;
+use crate::agent::Agent;
+use crate::balance::Balance;
+use crate::keystore::Keystore;
+
+use super::*;
+
+/// A set of named accounts, one of which is the default signer.
+///
+/// # Example
+/// ```ignore
+/// use aleo_agent::agent::Agent;
+/// use aleo_agent::wallet::Wallet;
+///
+/// // Loads `treasury.json`, `payroll.json`, ... from the directory
+/// let wallet = Wallet::load_dir("keystores", "password").expect("Failed to load wallet");
+/// let agent = Agent::builder().with_wallet(wallet).build();
+///
+/// let treasury = agent.with_signer("treasury").expect("Unknown account");
+/// println!("{}", treasury.get_public_balance().unwrap());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Wallet {
+    accounts: IndexMap<String, Account>,
+    default: Option<String>,
+}
+
+impl Wallet {
+    /// Creates an empty wallet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every keystore file (`<name>.json`) of a directory, naming each account after its file.
+    ///
+    /// The first account in alphabetical order becomes the default signer.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory holding the keystore files
+    /// * `password` - The password of the keystore files
+    pub fn load_dir<P: AsRef<Path>>(dir: P, password: &str) -> Result<Self> {
+        let mut files = fs::read_dir(dir.as_ref())?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        files.retain(|file| file.extension().and_then(|ext| ext.to_str()) == Some("json"));
+        files.sort();
+
+        let mut wallet = Self::new();
+        for file in files {
+            let name = file
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .ok_or_else(|| anyhow!("Invalid keystore file name {}", file.display()))?;
+            let account = Keystore::load(&file)?
+                .decrypt(password)
+                .map_err(|error| anyhow!("Failed to load {}: {error}", file.display()))?;
+            wallet.insert(name, account)?;
+        }
+        Ok(wallet)
+    }
+
+    /// Saves every account of the wallet to a keystore file (`<name>.json`) in a directory.
+    pub fn save_dir<P: AsRef<Path>>(&self, dir: P, password: &str) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        for (name, account) in &self.accounts {
+            account.save_keystore(dir.join(format!("{name}.json")), password)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a named account to the wallet.
+    ///
+    /// The first account added becomes the default signer. The name is used as a file name by
+    /// [save_dir](Wallet::save_dir), it must not be `.` or `..` nor contain a path separator.
+    pub fn insert<S: Into<String>>(&mut self, name: S, account: Account) -> Result<()> {
+        let name = name.into();
+        ensure!(!name.is_empty(), "The account name must not be empty");
+        ensure!(
+            name != "." && name != ".." && !name.contains(['/', '\\']),
+            "The account name '{name}' is not a valid file name"
+        );
+        ensure!(
+            !self.accounts.contains_key(&name),
+            "The wallet already holds an account named '{name}'"
+        );
+        if self.default.is_none() {
+            self.default = Some(name.clone());
+        }
+        self.accounts.insert(name, account);
+        Ok(())
+    }
+
+    /// Removes a named account from the wallet.
+    ///
+    /// If it was the default signer, the first remaining account becomes the default signer.
+    pub fn remove(&mut self, name: &str) -> Option<Account> {
+        let account = self.accounts.shift_remove(name)?;
+        if self.default.as_deref() == Some(name) {
+            self.default = self.accounts.keys().next().cloned();
+        }
+        Some(account)
+    }
+
+    /// Returns the account named `name`.
+    pub fn get(&self, name: &str) -> Option<&Account> {
+        self.accounts.get(name)
+    }
+
+    /// Returns the name of the default signer.
+    pub fn default_name(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// Returns the default signer.
+    pub fn default_account(&self) -> Option<&Account> {
+        self.default
+            .as_ref()
+            .and_then(|name| self.accounts.get(name))
+    }
+
+    /// Sets the default signer.
+    pub fn set_default(&mut self, name: &str) -> Result<()> {
+        if !self.accounts.contains_key(name) {
+            bail!("The wallet holds no account named '{name}'");
+        }
+        self.default = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Returns the names of the accounts.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.accounts.keys().map(String::as_str)
+    }
+
+    /// Returns the addresses of the accounts by name.
+    pub fn addresses(&self) -> IndexMap<String, Address> {
+        self.accounts
+            .iter()
+            .map(|(name, account)| (name.clone(), *account.address()))
+            .collect()
+    }
+
+    /// Returns the accounts by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Account)> {
+        self.accounts
+            .iter()
+            .map(|(name, account)| (name.as_str(), account))
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+impl Agent {
+    /// Returns a copy of the agent signing with the wallet account named `name`.
+    ///
+    /// The copy signs with the private key of the account, even if the agent has an external signer.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use aleo_agent::agent::{Agent, TransferArgs, TransferType};
+    /// use aleo_agent::wallet::Wallet;
+    /// use aleo_agent::MICROCREDITS;
+    ///
+    /// let wallet = Wallet::load_dir("keystores", "password").unwrap();
+    /// let agent = Agent::builder().with_wallet(wallet).build();
+    /// let recipient = *agent.wallet().unwrap().get("payroll").unwrap().address();
+    /// let args = TransferArgs::from(MICROCREDITS, recipient, 1, None, TransferType::Public);
+    /// agent.with_signer("treasury").unwrap().transfer(args).unwrap();
+    /// ```
+    pub fn with_signer(&self, name: &str) -> Result<Agent> {
+        let wallet = self
+            .wallet()
+            .ok_or_else(|| anyhow!("The agent has no wallet"))?;
+        let account = wallet
+            .get(name)
+            .ok_or_else(|| anyhow!("The wallet holds no account named '{name}'"))?;
+        let mut agent = self.clone();
+        agent.set_account(account.clone());
+        agent.remove_external_signer();
+        Ok(agent)
+    }
+
+    /// Fetches the balance of every wallet account, see [get_balance_of](Agent::get_balance_of).
+    ///
+    /// The records of watch-only accounts are reported as [unchecked](Balance::unchecked), as their
+    /// serial numbers cannot be derived.
+    ///
+    /// # Arguments
+    /// * `block_heights` - The range of block heights to scan for private records
+    ///
+    /// # Returns
+    /// The address and balance of each account, by name.
+    pub fn get_wallet_balances(
+        &self,
+        block_heights: Range<u32>,
+    ) -> Result<IndexMap<String, (Address, Balance)>> {
+        let wallet = self
+            .wallet()
+            .ok_or_else(|| anyhow!("The agent has no wallet"))?;
+        wallet
+            .iter()
+            .map(|(name, account)| {
+                let balance = match account.expose_private_key() {
+                    Ok(private_key) => {
+                        self.get_balance_of(account.view_key(), private_key, block_heights.clone())?
+                    }
+                    Err(_) => self.get_balance_of(
+                        account.view_key(),
+                        &IndexMap::<Field, Field>::new(),
+                        block_heights.clone(),
+                    )?,
+                };
+                Ok((name.to_string(), (*account.address(), balance)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wallet() {
+        let mut wallet = Wallet::new();
+        let treasury = Account::new().unwrap();
+        let payroll = Account::new().unwrap();
+        wallet.insert("treasury", treasury.clone()).unwrap();
+        wallet.insert("payroll", payroll.clone()).unwrap();
+        assert!(wallet.insert("payroll", Account::new().unwrap()).is_err());
+        for name in ["..", ".", "../payroll", "keys/payroll", "keys\\payroll"] {
+            assert!(wallet.insert(name, Account::new().unwrap()).is_err());
+        }
+
+        assert_eq!(wallet.default_name(), Some("treasury"));
+        assert!(wallet.set_default("unknown").is_err());
+        wallet.set_default("payroll").unwrap();
+        assert_eq!(
+            wallet.default_account().unwrap().address(),
+            payroll.address()
+        );
+
+        let addresses = wallet.addresses();
+        assert_eq!(addresses["treasury"], *treasury.address());
+        assert_eq!(addresses["payroll"], *payroll.address());
+
+        wallet.remove("payroll").unwrap();
+        assert_eq!(wallet.default_name(), Some("treasury"));
+
+        let agent = Agent::builder().with_wallet(wallet.clone()).build();
+        assert_eq!(agent.account().address(), treasury.address());
+        let signer = agent.with_signer("treasury").unwrap();
+        assert_eq!(signer.account().address(), treasury.address());
+        assert!(agent.with_signer("payroll").is_err());
+
+        let mut agent = Agent::default();
+        agent.set_wallet(wallet);
+        assert_eq!(agent.account().address(), treasury.address());
+    }
+}
@@ -0,0 +1,17 @@
+use std::env;
+use std::fs;
+use std::str::FromStr;
+
+use aleo_agent::codegen;
+use aleo_agent::Program;
+
+// Generates the client of `programs/names.aleo`, whose struct, record and function names clash
+// with the types of `aleo-agent` and with the client constructor, so that building the example
+// checks that generated clients compile.
+fn main() {
+    println!("cargo:rerun-if-changed=programs/names.aleo");
+    let source = fs::read_to_string("programs/names.aleo").unwrap();
+    let program = Program::from_str(&source).unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+    codegen::write_client(&program, format!("{out_dir}/names.rs")).unwrap();
+}
@@ -0,0 +1,27 @@
+use aleo_agent::codegen;
+use aleo_agent::program::ProgramManager;
+use anyhow::{bail, Result};
+use std::env;
+
+// A client generated at build time, see `build.rs`
+#[allow(dead_code)]
+mod names {
+    include!(concat!(env!("OUT_DIR"), "/names.rs"));
+}
+
+fn main() -> Result<()> {
+    // usage: codegen <leo build folder> <output file>
+    let args = env::args().collect::<Vec<_>>();
+    if args.len() != 3 {
+        bail!("usage: {} <program build directory> <output .rs file>", args[0]);
+    }
+
+    // The build folder is generated by using leo build in the Leo project.
+    let program = ProgramManager::load_program_from_path(&args[1])?;
+
+    // write a typed client for the program
+    codegen::write_client(&program, &args[2])?;
+    println!("Generated client for {} in {}", program.id(), args[2]);
+
+    Ok(())
+}
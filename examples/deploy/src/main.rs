@@ -33,8 +33,10 @@ fn main() -> Result<()> {
 
     // execute program
     let inputs = vec!["1u32", "2u32"];
-    let exec_tx_id = pm.execute_program("main", inputs.into_iter(), 1, None)?;
+    let receipt = pm.execute_program("main", inputs.into_iter(), 1, None)?;
+    let exec_tx_id = receipt.transaction_id().to_string();
     println!("Execute program with tx id : {exec_tx_id}");
+    println!("Execution outputs : {:?}", receipt.outputs()?);
 
     // waiting for broadcast and confirmation
     sleep(Duration::from_secs(60));